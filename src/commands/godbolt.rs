@@ -243,15 +243,10 @@ fn parse(args: &str) -> Result<(KeyValueArgs, String), CodeBlockError> {
 	let mut value = String::new();
 	// flag for in a key
 	let mut k = true;
-	let mut args = args.chars();
-	let mut tick_count = 0;
 	// note: you cant put a backtick in an argument
-	for ch in args.by_ref() {
+	let code_start = args.find('`').ok_or_else(CodeBlockError::default)?;
+	for ch in args[..code_start].chars() {
 		match ch {
-			'`' => {
-				tick_count += 1;
-				break;
-			}
 			' ' => {
 				map.insert(take(&mut key), take(&mut value));
 				k = true
@@ -263,24 +258,8 @@ fn parse(args: &str) -> Result<(KeyValueArgs, String), CodeBlockError> {
 	}
 
 	// note: language can be parsed, but is discarded here
-	let mut parsed_lang = false;
-	let mut code = String::new();
-	for ch in args {
-		match ch {
-			// ```
-			'`' if tick_count == 3 && !parsed_lang => return Err(CodeBlockError::default()),
-			// closing
-			'`' if tick_count == 3 && parsed_lang => break,
-			'`' => tick_count += 1,
-			// ```rust
-			//    ^^^^
-			'\n' if tick_count == 3 && !parsed_lang => parsed_lang = true,
-			_ if tick_count == 3 && !parsed_lang => {}
-
-			c => code.push(c),
-		}
-	}
-	Ok((KeyValueArgs(map), code))
+	let (_, code_block) = crate::helpers::extract_code(&args[code_start..])?;
+	Ok((KeyValueArgs(map), code_block.code))
 }
 
 /// View assembly using Godbolt
@@ -297,6 +276,7 @@ fn parse(args: &str) -> Result<(KeyValueArgs, String), CodeBlockError> {
 /// Optional arguments:
 /// - `flag*`: flags to pass to rustc invocation. Defaults to {"-Copt-level=3", "--edition=2021"}
 /// - `rustc`: compiler version to invoke. Defaults to `nightly`. Possible values: `nightly`, `beta` or full version like `1.45.2`
+/// - `debuginfo`: debug info level (`-Cdebuginfo`), `0`, `1` or `2`. Defaults to `0` for readable output
 #[poise::command(prefix_command, category = "Godbolt", broadcast_typing, track_edits)]
 pub async fn godbolt(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Error> {
 	let (params, code) = parse(&arguments)?;
@@ -313,6 +293,47 @@ pub async fn godbolt(ctx: Context<'_>, #[rest] arguments: String) -> Result<(),
 	respond_codeblocks(ctx, godbolt_result, godbolt_request, "x86asm", note).await
 }
 
+/// Wraps an expression in an exported function whose result is passed through
+/// `std::hint::black_box`, so the compiler can neither drop it nor inline it away
+fn asmstd_scaffold(expression: &str) -> String {
+	format!(
+		"#[no_mangle]\npub fn asmstd_stub() {{\n\tstd::hint::black_box({{\n{}\n\t}});\n}}\n",
+		expression.trim_end()
+	)
+}
+
+/// View the assembly of std functions using Godbolt
+///
+/// Compiles the given expression on <https://rust.godbolt.org> inside an exported stub function, \
+/// so you can see what std methods compile to without writing the `#[no_mangle]` scaffolding \
+/// yourself. Full optimizations are applied unless overriden.
+/// ```
+/// ?asmstd flag={} rustc={} ``​`
+/// let mut v = Vec::new();
+/// v.push(1u32);
+/// v
+/// ``​`
+/// ```
+/// Optional arguments:
+/// - `flag*`: flags to pass to rustc invocation. Defaults to {"-Copt-level=3", "--edition=2021"}
+/// - `rustc`: compiler version to invoke. Defaults to `nightly`. Possible values: `nightly`, `beta` or full version like `1.45.2`
+/// - `debuginfo`: debug info level (`-Cdebuginfo`), `0`, `1` or `2`. Defaults to `0` for readable output
+#[poise::command(prefix_command, category = "Godbolt", broadcast_typing, track_edits)]
+pub async fn asmstd(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Error> {
+	let (params, expression) = parse(&arguments)?;
+	let (rustc, flags) = rustc_id_and_flags(ctx.data(), &params).await?;
+	let code = asmstd_scaffold(&expression);
+	let godbolt_request = GodboltRequest {
+		source_code: &code,
+		rustc: &rustc,
+		flags: &flags,
+		run_llvm_mca: false,
+	};
+	let godbolt_result = compile_rust_source(&ctx.data().http, &godbolt_request).await?;
+
+	respond_codeblocks(ctx, godbolt_result, godbolt_request, "x86asm", "").await
+}
+
 /// Run performance analysis using llvm-mca
 ///
 /// Run the performance analysis tool llvm-mca using <https://rust.godbolt.org>. Full optimizations \
@@ -327,6 +348,7 @@ pub async fn godbolt(ctx: Context<'_>, #[rest] arguments: String) -> Result<(),
 /// Optional arguments:
 /// - `flag*`: flags to pass to rustc invocation. Defaults to {"-Copt-level=3", "--edition=2021"}
 /// - `rustc`: compiler version to invoke. Defaults to `nightly`. Possible values: `nightly`, `beta` or full version like `1.45.2`
+/// - `debuginfo`: debug info level (`-Cdebuginfo`), `0`, `1` or `2`. Defaults to `0` for readable output
 #[poise::command(prefix_command, category = "Godbolt", broadcast_typing, track_edits)]
 pub async fn mca(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Error> {
 	let (params, code) = parse(&arguments)?;
@@ -349,7 +371,7 @@ pub async fn mca(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Erro
 /// Compile Rust code using <https://rust.godbolt.org> and emits LLVM IR. Full optimizations \
 /// are applied unless overriden.
 ///
-/// Equivalent to ?godbolt but with extra flags `--emit=llvm-ir -Cdebuginfo=0`.
+/// Equivalent to ?godbolt but with the extra flag `--emit=llvm-ir`.
 /// ```
 /// ?llvmir flag={} rustc={} ``​`
 /// pub fn your_function() {
@@ -358,16 +380,22 @@ pub async fn mca(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Erro
 /// ``​`
 /// ```
 /// Optional arguments:
-/// - `flag*`: flags to pass to rustc invocation. Defaults to {"-Copt-level=3", "--edition=2021", "--emit=llvm-ir", "-Cdebuginfo=0"}
+/// - `flag*`: flags to pass to rustc invocation. Defaults to {"-Copt-level=3", "--edition=2021", "--emit=llvm-ir"}
 /// - `rustc`: compiler version to invoke. Defaults to `nightly`. Possible values: `nightly`, `beta` or full version like `1.45.2`
+/// - `debuginfo`: debug info level (`-Cdebuginfo`), `0`, `1` or `2`. Defaults to `0` for readable output
+/// - `llvmdiff`: `true` to show how the IR at `-Copt-level=2` differs from `-Copt-level=0`
 #[poise::command(prefix_command, category = "Godbolt", broadcast_typing, track_edits)]
 pub async fn llvmir(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), Error> {
-	let (params, code) = parse(&arguments)?;
+	let (mut params, code) = parse(&arguments)?;
+	// Not a rustc flag, so it mustn't be passed on
+	if params.0.remove("llvmdiff").as_deref() == Some("true") {
+		return llvmir_diff(ctx, params, &code).await;
+	}
 	let (rustc, flags) = rustc_id_and_flags(ctx.data(), &params).await?;
 	let godbolt_request = GodboltRequest {
 		source_code: &code,
 		rustc: &rustc,
-		flags: &(flags + " --emit=llvm-ir -Cdebuginfo=0"),
+		flags: &(flags + " --emit=llvm-ir"),
 		run_llvm_mca: false,
 	};
 	let godbolt_result = compile_rust_source(&ctx.data().http, &godbolt_request).await?;
@@ -375,3 +403,44 @@ pub async fn llvmir(ctx: Context<'_>, #[rest] arguments: String) -> Result<(), E
 	let note = note(&code);
 	respond_codeblocks(ctx, godbolt_result, godbolt_request, "llvm", note).await
 }
+
+/// Emits LLVM IR without and with optimizations and replies with the difference, to show what
+/// LLVM's optimizations do
+async fn llvmir_diff(ctx: Context<'_>, mut params: KeyValueArgs, code: &str) -> Result<(), Error> {
+	params.0.insert("-Copt-level".into(), "0".into());
+	let (rustc, unoptimized_flags) = rustc_id_and_flags(ctx.data(), &params).await?;
+	params.0.insert("-Copt-level".into(), "2".into());
+	let (_, optimized_flags) = rustc_id_and_flags(ctx.data(), &params).await?;
+
+	let (unoptimized_flags, optimized_flags) = (
+		unoptimized_flags + " --emit=llvm-ir",
+		optimized_flags + " --emit=llvm-ir",
+	);
+	let unoptimized_request = GodboltRequest {
+		source_code: code,
+		rustc: &rustc,
+		flags: &unoptimized_flags,
+		run_llvm_mca: false,
+	};
+	let optimized_request = GodboltRequest {
+		flags: &optimized_flags,
+		..unoptimized_request
+	};
+	let (unoptimized, optimized) = poise::futures_util::future::join(
+		compile_rust_source(&ctx.data().http, &unoptimized_request),
+		compile_rust_source(&ctx.data().http, &optimized_request),
+	)
+	.await;
+	let (unoptimized, optimized) = (unoptimized?, optimized?);
+
+	// Errors are the same at both levels, so the unoptimized compilation shows them
+	if unoptimized.output.trim() == "<Compilation failed>" {
+		return respond_codeblocks(ctx, unoptimized, unoptimized_request, "llvm", "").await;
+	}
+
+	let diff = match crate::helpers::diff_lines(&unoptimized.output, &optimized.output) {
+		Some(diff) => format!("--- -Copt-level=0\n+++ -Copt-level=2\n{diff}"),
+		None => "LLVM IR is the same with and without optimizations\n".to_owned(),
+	};
+	respond_codeblock(ctx, "diff", &diff, note(code), &optimized_request).await
+}
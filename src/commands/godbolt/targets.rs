@@ -1,8 +1,8 @@
 use std::iter::once;
 
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, bail, Error};
 use poise::serenity_prelude as serenity;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 use crate::types::Context;
 use crate::types::Data;
@@ -149,16 +149,28 @@ pub(crate) async fn rustc_id_and_flags(
 
 	let opt_level = params.get("-Copt-level").unwrap_or("3");
 	let edition = params.get("--edition").unwrap_or("2021");
+	// Debug info clutters the asm with `.loc` and `.cfi` directives, so it's off unless requested
+	let debuginfo = params
+		.get("debuginfo")
+		.or_else(|| params.get("-Cdebuginfo"))
+		.unwrap_or("0");
+	if !matches!(debuginfo, "0" | "1" | "2") {
+		bail!("the `debuginfo` argument should be 0, 1 or 2");
+	}
 	let flags = itertools::Itertools::intersperse(params
 		.0
 		.iter()
-		.filter(|(k, _)| !matches!(k.as_str(), "rustc" | "-Copt-level" | "--edition"))
+		.filter(|(k, _)| !matches!(
+			k.as_str(),
+			"rustc" | "-Copt-level" | "--edition" | "debuginfo" | "-Cdebuginfo"
+		))
 		.map(|(a, b)| format!("{a}={b}"))
 		.chain(once(format!("-Copt-level={opt_level}")))
-		.chain(once(format!("--edition={edition}"))), " ".to_string())
+		.chain(once(format!("--edition={edition}")))
+		.chain(once(format!("-Cdebuginfo={debuginfo}"))), " ".to_string())
 		// itertools was already imported by prost
 		.collect::<String>();
-	println!("{flags}");
+	debug!("godbolt compiler flags: {flags}");
 
 	Ok((target.id, flags))
 }
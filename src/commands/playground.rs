@@ -1,13 +1,31 @@
 //! run rust code on the rust-lang playground
 
+pub use cache::ResultCache;
+pub use debounce::EditDebounce;
+pub use funcs::*;
+pub use health::*;
 pub use microbench::*;
 pub use misc_commands::*;
+pub use permalink::*;
 pub use play_eval::*;
 pub use procmacro::*;
 
+pub use api::Channel;
+
 mod api;
+mod cache;
+mod debounce;
+mod examples;
+mod funcs;
+mod glossary;
+mod health;
+mod inject;
 mod microbench;
 mod misc_commands;
+mod permalink;
 mod play_eval;
 mod procmacro;
+mod qr;
+mod render;
+mod templates;
 mod util;
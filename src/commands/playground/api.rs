@@ -1,21 +1,312 @@
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Error};
 use reqwest::header;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use tracing::info;
 
+use crate::config::Config;
 use crate::types::Context;
 
+use super::templates::Template;
+
+#[allow(clippy::struct_excessive_bools)] // most flags are simple on/off switches
+#[derive(Default)]
 pub struct CommandFlags {
 	pub channel: Channel,
 	pub mode: Mode,
 	pub edition: Edition,
 	pub warn: bool,
 	pub run: bool,
+	pub template: Option<Template>,
+	pub binsize: bool,
+	pub phasetime: bool,
+	pub maxerrors: Option<std::num::NonZeroUsize>,
+	pub withtests: bool,
+	pub icefallback: bool,
+	pub embed: bool,
+	pub allchannels: bool,
+	pub merge: bool,
+	pub showtype: bool,
+	pub tail: Option<std::num::NonZeroUsize>,
+	pub autouse: bool,
+	pub stacksize: Option<std::num::NonZeroUsize>,
+	pub migrate: Option<Edition>,
+	pub dedupe: bool,
+	pub pin: bool,
+	pub hash: bool,
+	pub ephemeral: bool,
+	pub safety: bool,
+	pub autoderive: bool,
+	pub webhook: bool,
+	pub groupwarn: bool,
+	pub fmtgist: bool,
+	pub issue: bool,
+	pub deadline: Option<std::num::NonZeroU64>,
+	pub optcompare: bool,
+	pub silent: bool,
+	pub miridiff: bool,
+	pub permalink: bool,
+	pub clock: bool,
+	pub style: bool,
+	pub seed: Option<u64>,
+	pub unsafecheck: bool,
+	pub cpus: Option<std::num::NonZeroUsize>,
+	pub forbid: ForbiddenCrates,
+	pub image: bool,
+	pub panicdemo: bool,
+	pub showtoolchain: bool,
+	pub stripdocs: bool,
+	pub prettypanic: bool,
+	pub allocs: bool,
+	pub optmir: bool,
+	pub netcheck: bool,
+	pub explainoutput: bool,
+	pub wrap: Option<std::num::NonZeroUsize>,
+	pub value: bool,
+	pub qr: bool,
+	pub suggest: bool,
+	pub denywarnings: bool,
+	pub histogram: bool,
+	pub editions: Option<Editions>,
+	pub nocache: bool,
+	pub diffprev: bool,
+	pub collapse: bool,
+	pub asynccheck: bool,
+	pub cargotoml: bool,
+	pub replay: bool,
+	pub backend: CodegenBackend,
+	pub maxwarn: Option<std::num::NonZeroUsize>,
+	pub safe: bool,
+}
+
+impl CommandFlags {
+	/// Short description of the toolchain settings, e.g. `channel=nightly mode=debug edition=2021`
+	#[must_use]
+	pub fn toolchain_summary(&self) -> String {
+		format!(
+			"channel={} mode={} edition={}",
+			self.channel.name(),
+			self.mode.name(),
+			self.edition.name()
+		)
+	}
+
+	/// Comment line describing the toolchain, e.g. `// nightly, debug, edition 2021`, shown at the
+	/// top of the output with `showtoolchain=true`
+	#[must_use]
+	pub fn toolchain_comment(&self) -> String {
+		format!(
+			"// {}, {}, edition {}\n",
+			self.channel.name(),
+			self.mode.name(),
+			self.edition.name()
+		)
+	}
+
+	/// The flags as `key=value` arguments that make `?play` run with them again, for
+	/// `replay=true`. Flags that are off are left out, except for the toolchain settings, whose
+	/// defaults may change
+	#[must_use]
+	pub fn replay_args(&self) -> String {
+		let mut args = self.toolchain_summary();
+		let mut push = |name: &str, value: &dyn std::fmt::Display| {
+			let _ = write!(args, " {name}={value}");
+		};
+
+		if let Some(template) = self.template {
+			push("template", &template.name());
+		}
+		if let Some(migrate) = self.migrate {
+			push("migrate", &migrate.name());
+		}
+		if !self.forbid.0.is_empty() {
+			push("forbid", &self.forbid.0.join(","));
+		}
+		if let Some(editions) = &self.editions {
+			let names = editions.0.iter().map(|edition| edition.name());
+			push("editions", &names.collect::<Vec<_>>().join(","));
+		}
+		for flag in NUMBER_FLAGS {
+			if let Some(value) = (flag.get)(self) {
+				push(flag.name, &value);
+			}
+		}
+		if let Some(deadline) = self.deadline {
+			push("deadline", &deadline);
+		}
+		if let Some(seed) = self.seed {
+			push("seed", &seed);
+		}
+
+		// Replaying `replay=true` would only repeat the footer it's read from
+		for flag in BOOL_FLAGS.iter().filter(|flag| flag.name != "replay") {
+			if (flag.get)(self) {
+				push(flag.name, &true);
+			}
+		}
+		args
+	}
+}
+
+/// A [`CommandFlags`] switch, like `warn=true`
+pub struct BoolFlag {
+	pub name: &'static str,
+	/// Description for `?playflags`, or `None` if the commands' own help already lists the flag
+	pub help: Option<&'static str>,
+	pub get: fn(&CommandFlags) -> bool,
+	pub get_mut: fn(&mut CommandFlags) -> &mut bool,
+}
+
+/// A [`CommandFlags`] count, like `tail=20`
+pub struct NumberFlag {
+	pub name: &'static str,
+	/// Full `?playflags` entry after the name, or `None` if it's documented elsewhere
+	pub help: Option<&'static str>,
+	pub get: fn(&CommandFlags) -> Option<std::num::NonZeroUsize>,
+	pub get_mut: fn(&mut CommandFlags) -> &mut Option<std::num::NonZeroUsize>,
+}
+
+macro_rules! flag_table {
+	($flag_type:ident { $($field:ident: $help:expr,)* }) => {
+		&[$($flag_type {
+			name: stringify!($field),
+			help: $help,
+			get: |flags| flags.$field,
+			get_mut: |flags| &mut flags.$field,
+		},)*]
+	};
 }
 
+/// The on/off flags. Parsing, `replay=true` and `?playflags` all go by this table, so adding a
+/// flag here is enough for all three
+pub const BOOL_FLAGS: &[BoolFlag] = flag_table!(BoolFlag {
+	warn: None,
+	run: None,
+	binsize: Some(
+		"Show the size of the wasm build. The playground only returns its text format, which is \
+		several times larger than the binary",
+	),
+	phasetime: Some(
+		"Show how long the request took. The playground doesn't report compile and run time \
+		separately, so this is the total of both",
+	),
+	clock: Some(
+		"Show the total run time. Individual lines can't be timestamped, as the playground returns \
+		all output at once when the program exits",
+	),
+	style: Some("Check whether the code is clippy-clean and formatted with rustfmt"),
+	optmir: Some("Show how optimizations change the MIR, as a diff of the debug and release MIR"),
+	panicdemo: Some(
+		"Run in both debug and release mode and explain how they behaved differently, e.g. when an \
+		overflow only panics in debug mode",
+	),
+	withtests: Some("Run the program, then its `#[test]` functions, and show both"),
+	icefallback: Some("If nightly crashes with an internal compiler error, run on stable instead"),
+	showtoolchain: Some(
+		"Start the output with a comment naming the channel, mode and edition, so screenshots of \
+		it are self-explanatory",
+	),
+	embed: Some("Reply with an embed instead of a code block"),
+	image: Some(
+		"Reply with the output rendered as an image, including colors. Long output is cut off",
+	),
+	allchannels: Some("Run on stable, beta and nightly and show which ones succeed"),
+	merge: Some(
+		"Show stderr and stdout as labeled sections. Their relative order can't be recovered",
+	),
+	safe: Some(
+		"Break up `@everyone`, `@here` and role mentions in the output and hide link previews, for \
+		bots relaying the reply",
+	),
+	collapse: Some("Hide the output behind a spoiler until it's clicked"),
+	diffprev: Some("Show how the output differs from the previous run in this channel"),
+	nocache: Some(
+		"Run the code again even if the bot still has a result for it from an identical run",
+	),
+	histogram: Some("Show a histogram of how long the lines of stdout are"),
+	denywarnings: Some(
+		"Turn warnings into errors with `#![deny(warnings)]`, so the code only runs if it compiles \
+		cleanly",
+	),
+	suggest: Some(
+		"Show how the code changes when applying Clippy's short suggestions, without running the \
+		result",
+	),
+	qr: Some("Attach a QR code of the playground link, for opening it on a phone"),
+	value: Some("Print the value of the expression `fn main` ends in, like `?eval` does"),
+	autouse: Some("Import common std items like `HashMap` or `Rc` if missing"),
+	autoderive: Some(
+		"Add `#[derive(Debug)]` to structs and enums lacking it, so `?eval` can print them",
+	),
+	netcheck: Some("Warn if the code uses networking APIs, which are blocked on the playground"),
+	cargotoml: Some(
+		"Show a `Cargo.toml` with the edition and the playground crates the code uses, for running \
+		it locally",
+	),
+	replay: Some("Show the command with all flags in effect that runs the code again the same way"),
+	asynccheck: Some(
+		"Warn about blocking calls like `std::thread::sleep` inside async functions and blocks",
+	),
+	unsafecheck: Some("Warn about how often the code uses `unsafe`"),
+	groupwarn: Some("Show warnings, preceded by how many there are of each lint"),
+	explainoutput: Some("Explain common panics and errors in the output in plain English"),
+	dedupe: Some("Collapse identical consecutive lines of stdout into one"),
+	allocs: Some("Count the heap allocations made by `main` and print the count once it returns"),
+	prettypanic: Some(
+		"Print just the message of a panic, to stdout, instead of the usual stderr output",
+	),
+	hash: Some(
+		"Show a short hash of the code and toolchain settings, to tell whether two runs ran the \
+		same thing",
+	),
+	ephemeral: Some("Only show the result to you. Slash commands only"),
+	safety: Some(
+		"Also run the code in Miri to check for undefined behavior, and summarize both runs",
+	),
+	webhook: Some(
+		"Also post the result to the bot's webhook, if one is configured. Moderators only",
+	),
+	fmtgist: Some("Run rustfmt on the code before sharing it when the output is too large"),
+	stripdocs: Some(
+		"Remove `///`, `//!`, `/** */` and `/*! */` doc comments from the code shared in \
+		playground links. The code that's run keeps them",
+	),
+	issue: Some(
+		"Reply with a ready to paste GitHub issue body containing the code, compiler version, \
+		output and a playground link",
+	),
+	optcompare: Some("Compare the amount of assembly of debug and release builds"),
+	miridiff: Some("Also run the code in Miri and show how its stdout differs from the normal run"),
+	permalink: Some(
+		"Store the code with the bot, so it can be retrieved with `?get` even after playground \
+		links expire, if enabled by the operator",
+	),
+	silent: Some(
+		"If the code runs successfully without any output, only react instead of replying",
+	),
+	pin: Some("Pin the result. Moderators only"),
+	showtype: Some("`?eval` only: also print the type of the expression"),
+});
+
+/// The flags taking a positive count, used like [`BOOL_FLAGS`]
+pub const NUMBER_FLAGS: &[NumberFlag] = flag_table!(NumberFlag {
+	maxerrors: None,
+	maxwarn: Some("show only the first N compiler warnings (default: all)"),
+	tail: Some("show only the last N lines of output (default: all)"),
+	wrap: Some("break output lines longer than N characters into several (default: no wrapping)"),
+	cpus: Some(
+		"define `const NUM_CPUS: usize = N;`. The playground decides how many cores \
+		`available_parallelism()` reports, so concurrency demos can use this instead (default: \
+		none)",
+	),
+	stacksize: Some(
+		"run `main` on a thread with an N MiB stack, for deep recursion (default: none)",
+	),
+});
+
 #[derive(Debug, Serialize)]
 pub struct PlaygroundRequest<'a> {
 	pub channel: Channel,
@@ -27,6 +318,25 @@ pub struct PlaygroundRequest<'a> {
 	pub tests: bool,
 }
 
+impl PlaygroundRequest<'_> {
+	/// Short, stable hash of everything that influences the result of running this request,
+	/// i.e. the code and the toolchain settings
+	#[must_use]
+	pub fn content_hash(&self) -> String {
+		use sha2::Digest as _;
+
+		// Struct fields are always serialized in the same order, so this is deterministic
+		let serialized = serde_json::to_vec(self).expect("playground requests always serialize");
+		sha2::Sha256::digest(serialized)
+			.iter()
+			.take(6)
+			.fold(String::new(), |mut hash, byte| {
+				let _ = write!(hash, "{byte:02x}");
+				hash
+			})
+	}
+}
+
 #[derive(Debug, Serialize)]
 pub struct MiriRequest<'a> {
 	pub edition: Edition,
@@ -100,25 +410,41 @@ pub enum ProcessAssembly {
 	Raw,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 #[allow(unused)]
 pub enum CompileTarget {
+	Asm,
 	Mir,
+	Wasm,
 }
 
-#[allow(unused)]
 pub type CompileResponse = FormatResponse;
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Default, Clone, Copy, Serialize, poise::ChoiceParameter)]
 #[serde(rename_all = "snake_case")]
 #[allow(unused)]
 pub enum Channel {
+	#[name = "stable"]
 	Stable,
+	#[name = "beta"]
 	Beta,
+	#[default]
+	#[name = "nightly"]
 	Nightly,
 }
 
+impl Channel {
+	#[must_use]
+	pub fn name(self) -> &'static str {
+		match self {
+			Channel::Stable => "stable",
+			Channel::Beta => "beta",
+			Channel::Nightly => "nightly",
+		}
+	}
+}
+
 impl FromStr for Channel {
 	type Err = Error;
 
@@ -132,18 +458,37 @@ impl FromStr for Channel {
 	}
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, poise::ChoiceParameter,
+)]
 pub enum Edition {
 	#[serde(rename = "2015")]
+	#[name = "2015"]
 	E2015,
 	#[serde(rename = "2018")]
+	#[name = "2018"]
 	E2018,
+	#[default]
 	#[serde(rename = "2021")]
+	#[name = "2021"]
 	E2021,
 	#[serde(rename = "2024")]
+	#[name = "2024"]
 	E2024,
 }
 
+impl Edition {
+	#[must_use]
+	pub fn name(self) -> &'static str {
+		match self {
+			Edition::E2015 => "2015",
+			Edition::E2018 => "2018",
+			Edition::E2021 => "2021",
+			Edition::E2024 => "2024",
+		}
+	}
+}
+
 impl FromStr for Edition {
 	type Err = Error;
 
@@ -167,13 +512,26 @@ pub enum CrateType {
 	Library,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Default, Clone, Copy, Serialize, poise::ChoiceParameter)]
 #[serde(rename_all = "snake_case")]
 pub enum Mode {
+	#[default]
+	#[name = "debug"]
 	Debug,
+	#[name = "release"]
 	Release,
 }
 
+impl Mode {
+	#[must_use]
+	pub fn name(self) -> &'static str {
+		match self {
+			Mode::Debug => "debug",
+			Mode::Release => "release",
+		}
+	}
+}
+
 impl FromStr for Mode {
 	type Err = Error;
 
@@ -186,11 +544,173 @@ impl FromStr for Mode {
 	}
 }
 
-#[derive(Debug)]
+/// Level passed to rustc's `--cap-lints`, which limits how severe any lint can get. Unlike
+/// `#![warn(...)]` style attributes, it also overrides `deny` and `forbid`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapLints {
+	Allow,
+	Warn,
+	Deny,
+}
+
+impl CapLints {
+	#[must_use]
+	pub fn name(self) -> &'static str {
+		match self {
+			CapLints::Allow => "allow",
+			CapLints::Warn => "warn",
+			CapLints::Deny => "deny",
+		}
+	}
+}
+
+impl FromStr for CapLints {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		match s {
+			"allow" => Ok(CapLints::Allow),
+			"warn" => Ok(CapLints::Warn),
+			"deny" => Ok(CapLints::Deny),
+			_ => bail!("invalid lint cap `{}`", s),
+		}
+	}
+}
+
+/// Codegen backend requested with `backend=...`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CodegenBackend {
+	#[default]
+	Llvm,
+	Cranelift,
+}
+
+impl FromStr for CodegenBackend {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		match s {
+			"llvm" => Ok(CodegenBackend::Llvm),
+			"cranelift" => Ok(CodegenBackend::Cranelift),
+			_ => bail!("invalid codegen backend `{}`", s),
+		}
+	}
+}
+
+/// Codegen flags which may be passed with `rustflags=...`. Anything else is rejected, so that
+/// users can't inject arbitrary compiler flags
+const ALLOWED_RUSTFLAGS: &[&str] = &[
+	"-C opt-level=0",
+	"-C opt-level=1",
+	"-C opt-level=2",
+	"-C opt-level=3",
+	"-C opt-level=s",
+	"-C opt-level=z",
+	"-C target-cpu=native",
+	"-C debug-assertions=on",
+	"-C debug-assertions=off",
+	"-C overflow-checks=on",
+	"-C overflow-checks=off",
+];
+
+/// Allowlisted rustc flags, parsed from a comma or space separated list like
+/// `-Copt-level=z,-C target-cpu=native`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RustFlags(pub Vec<&'static str>);
+
+impl RustFlags {
+	/// Applies the flags that correspond to a playground setting. Returns the flags that the
+	/// playground has no way to apply
+	pub fn apply_to(&self, flags: &mut CommandFlags) -> Vec<&'static str> {
+		let mut unsupported = Vec::new();
+		for &flag in &self.0 {
+			match flag {
+				"-C opt-level=0" => flags.mode = Mode::Debug,
+				"-C opt-level=3" => flags.mode = Mode::Release,
+				other => unsupported.push(other),
+			}
+		}
+		unsupported
+	}
+}
+
+impl FromStr for RustFlags {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		let mut parsed = Vec::new();
+		let mut tokens = s.split([',', ' ']).filter(|token| !token.is_empty());
+		while let Some(token) = tokens.next() {
+			// Accept both `-Cfoo=bar` and `-C foo=bar`
+			let codegen_option = match token.strip_prefix("-C") {
+				Some("") => tokens.next().unwrap_or(""),
+				Some(option) => option,
+				None => bail!("rustflag `{}` isn't allowed, only `-C` options are", token),
+			};
+			let flag = format!("-C {codegen_option}");
+			match ALLOWED_RUSTFLAGS.iter().find(|&&allowed| allowed == flag) {
+				Some(&allowed) => parsed.push(allowed),
+				None => bail!("rustflag `{}` isn't allowed, see `?playflags`", flag),
+			}
+		}
+		Ok(Self(parsed))
+	}
+}
+
+/// Editions to run the code on, parsed from a comma separated list like `2018,2021`. Duplicates
+/// are removed and the editions sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editions(pub Vec<Edition>);
+
+impl FromStr for Editions {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		let mut editions = s
+			.split(',')
+			.map(str::trim)
+			.filter(|edition| !edition.is_empty())
+			.map(str::parse)
+			.collect::<Result<Vec<Edition>, Error>>()?;
+		if editions.is_empty() {
+			bail!("`editions` needs at least one edition");
+		}
+		editions.sort();
+		editions.dedup();
+		Ok(Self(editions))
+	}
+}
+
+/// Crates the code may not use, parsed from a comma separated list like `rand,serde`. Crate
+/// names are normalized to how they're referred to in code, i.e. with underscores
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForbiddenCrates(pub Vec<String>);
+
+impl FromStr for ForbiddenCrates {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		let mut crates = Vec::new();
+		for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+			if !name
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+			{
+				bail!("invalid crate name `{}`", name);
+			}
+			crates.push(name.replace('-', "_"));
+		}
+		Ok(Self(crates))
+	}
+}
+
+#[derive(Debug, Clone)]
 pub struct PlayResult {
 	pub success: bool,
 	pub stdout: String,
 	pub stderr: String,
+	/// How the program exited, like `exit status: 1` or `signal: 9 (SIGKILL)`, if known
+	pub exit_detail: String,
 }
 
 impl<'de> Deserialize<'de> for PlayResult {
@@ -208,6 +728,8 @@ impl<'de> Deserialize<'de> for PlayResult {
 				success: bool,
 				stdout: String,
 				stderr: String,
+				#[serde(rename = "exitDetail", default)]
+				exit_detail: String,
 			},
 		}
 
@@ -216,20 +738,94 @@ impl<'de> Deserialize<'de> for PlayResult {
 				success,
 				stdout,
 				stderr,
+				exit_detail,
 			} => PlayResult {
 				success,
 				stdout,
 				stderr,
+				exit_detail,
 			},
 			RawPlayResponse::Err { error } => PlayResult {
 				success: false,
 				stdout: String::new(),
 				stderr: error,
+				exit_detail: String::new(),
 			},
 		})
 	}
 }
 
+/// Sends a request to the given playground endpoint and parses the response. Whether the
+/// playground answered is recorded for `?playhealth`
+pub async fn post_json<T: DeserializeOwned>(
+	ctx: Context<'_>,
+	endpoint: &str,
+	request: &impl Serialize,
+) -> Result<T, Error> {
+	let response = async {
+		Ok(ctx
+			.data()
+			.http
+			.post(ctx.data().config.playground_endpoint(endpoint))
+			.json(request)
+			.send()
+			.await?
+			.json()
+			.await?)
+	}
+	.await;
+
+	ctx.data()
+		.playground_health
+		.lock()
+		.unwrap()
+		.record(response.is_ok());
+	response
+}
+
+/// Compiles and runs the code on the playground
+pub async fn execute(
+	ctx: Context<'_>,
+	request: &PlaygroundRequest<'_>,
+) -> Result<PlayResult, Error> {
+	post_json(ctx, "execute", request).await
+}
+
+/// Runs the code in the Miri interpreter on the playground
+pub async fn execute_miri(
+	ctx: Context<'_>,
+	request: &MiriRequest<'_>,
+) -> Result<PlayResult, Error> {
+	post_json(ctx, "miri", request).await
+}
+
+/// Result of a playground run as posted to the configured webhook for `webhook=true`
+#[derive(Debug, Serialize)]
+pub struct WebhookPayload<'a> {
+	pub code: &'a str,
+	pub channel: Channel,
+	pub mode: Mode,
+	pub edition: Edition,
+	pub success: bool,
+	pub stdout: &'a str,
+	pub stderr: &'a str,
+}
+
+pub async fn post_webhook(
+	ctx: Context<'_>,
+	url: &str,
+	payload: &WebhookPayload<'_>,
+) -> Result<(), Error> {
+	ctx.data()
+		.http
+		.post(url)
+		.json(payload)
+		.send()
+		.await?
+		.error_for_status()?;
+	Ok(())
+}
+
 /// Returns a gist ID
 pub async fn post_gist(ctx: Context<'_>, code: &str) -> Result<String, Error> {
 	let mut payload = HashMap::new();
@@ -238,8 +834,8 @@ pub async fn post_gist(ctx: Context<'_>, code: &str) -> Result<String, Error> {
 	let resp = ctx
 		.data()
 		.http
-		.post("https://play.rust-lang.org/meta/gist/")
-		.header(header::REFERER, "https://discord.gg/rust-lang-community")
+		.post(ctx.data().config.playground_endpoint("meta/gist/"))
+		.header(header::REFERER, &ctx.data().config.playground_referer)
 		.json(&payload)
 		.send()
 		.await?;
@@ -251,24 +847,49 @@ pub async fn post_gist(ctx: Context<'_>, code: &str) -> Result<String, Error> {
 	Ok(gist_id)
 }
 
-pub fn url_from_gist(flags: &CommandFlags, gist_id: &str) -> String {
+/// Version of the compiler on one of the playground's release channels
+#[derive(Debug, Deserialize)]
+pub struct VersionResponse {
+	pub version: String,
+	pub hash: String,
+	pub date: String,
+}
+
+pub async fn fetch_version(ctx: Context<'_>, channel: Channel) -> Result<VersionResponse, Error> {
+	let url = ctx
+		.data()
+		.config
+		.playground_endpoint(&format!("meta/version/{}", channel.name()));
+	Ok(ctx.data().http.get(url).send().await?.json().await?)
+}
+
+/// One of the crates available on the playground
+#[derive(Debug, Deserialize)]
+pub struct CrateInfo {
+	pub name: String,
+	pub version: String,
+	/// Name of the library, which code refers to it by
+	pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CratesResponse {
+	crates: Vec<CrateInfo>,
+}
+
+pub async fn fetch_crates(ctx: Context<'_>) -> Result<Vec<CrateInfo>, Error> {
+	let url = ctx.data().config.playground_endpoint("meta/crates");
+	let response: CratesResponse = ctx.data().http.get(url).send().await?.json().await?;
+	Ok(response.crates)
+}
+
+pub fn url_from_gist(config: &Config, flags: &CommandFlags, gist_id: &str) -> String {
 	format!(
-		"https://play.rust-lang.org/?version={}&mode={}&edition={}&gist={}",
-		match flags.channel {
-			Channel::Nightly => "nightly",
-			Channel::Beta => "beta",
-			Channel::Stable => "stable",
-		},
-		match flags.mode {
-			Mode::Debug => "debug",
-			Mode::Release => "release",
-		},
-		match flags.edition {
-			Edition::E2015 => "2015",
-			Edition::E2018 => "2018",
-			Edition::E2021 => "2021",
-			Edition::E2024 => "2024",
-		},
+		"{}/?version={}&mode={}&edition={}&gist={}",
+		config.playground_url,
+		flags.channel.name(),
+		flags.mode.name(),
+		flags.edition.name(),
 		gist_id
 	)
 }
@@ -278,19 +899,104 @@ pub async fn apply_online_rustfmt(
 	code: &str,
 	edition: Edition,
 ) -> Result<PlayResult, Error> {
-	let result = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/format")
-		.json(&FormatRequest { code, edition })
-		.send()
-		.await?
-		.json::<FormatResponse>()
-		.await?;
+	let result: FormatResponse = post_json(ctx, "format", &FormatRequest { code, edition }).await?;
 
 	Ok(PlayResult {
 		success: result.success,
 		stdout: result.code,
 		stderr: result.stderr,
+		exit_detail: String::new(),
 	})
 }
+
+/// Compiles the code to the given target using the flags' channel, edition and mode, without
+/// running it
+pub async fn compile_to_target(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &CommandFlags,
+	target: CompileTarget,
+) -> Result<CompileResponse, Error> {
+	compile_to_target_in_mode(ctx, code, flags, target, flags.mode).await
+}
+
+/// Like [`compile_to_target`], but in the given mode instead of the flags' one
+pub async fn compile_to_target_in_mode(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &CommandFlags,
+	target: CompileTarget,
+	mode: Mode,
+) -> Result<CompileResponse, Error> {
+	post_json(
+		ctx,
+		"compile",
+		&CompileRequest {
+			assembly_flavor: AssemblyFlavour::default(),
+			backtrace: false,
+			channel: flags.channel,
+			code,
+			crate_type: CrateType::Binary,
+			demangle_assembly: DemangleAssembly::default(),
+			edition: flags.edition,
+			mode,
+			process_assembly: ProcessAssembly::default(),
+			target,
+			tests: false,
+		},
+	)
+	.await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn request(code: &str) -> PlaygroundRequest<'_> {
+		PlaygroundRequest {
+			channel: Channel::Nightly,
+			edition: Edition::E2021,
+			code,
+			crate_type: CrateType::Binary,
+			mode: Mode::Debug,
+			tests: false,
+		}
+	}
+
+	#[test]
+	fn identical_requests_hash_the_same() {
+		let code = "fn main() {}";
+		assert_eq!(request(code).content_hash(), request(code).content_hash());
+		assert_eq!(request(code).content_hash().len(), 12);
+	}
+
+	#[test]
+	fn code_and_settings_change_the_hash() {
+		let hash = request("fn main() {}").content_hash();
+		assert_ne!(hash, request("fn main() { }").content_hash());
+		assert_ne!(
+			hash,
+			PlaygroundRequest {
+				mode: Mode::Release,
+				..request("fn main() {}")
+			}
+			.content_hash()
+		);
+		assert_ne!(
+			hash,
+			PlaygroundRequest {
+				channel: Channel::Stable,
+				..request("fn main() {}")
+			}
+			.content_hash()
+		);
+		assert_ne!(
+			hash,
+			PlaygroundRequest {
+				tests: true,
+				..request("fn main() {}")
+			}
+			.content_hash()
+		);
+	}
+}
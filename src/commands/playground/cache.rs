@@ -0,0 +1,91 @@
+//! Remembers recent playground results, so running the same code with the same settings again
+//! shortly after doesn't hit the playground again
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::api::PlayResult;
+
+/// Least recently stored results are dropped beyond this, to bound memory usage
+const MAX_ENTRIES: usize = 100;
+
+/// Results keyed by the content hash of the request that produced them
+#[derive(Debug, Default)]
+pub struct ResultCache {
+	entries: HashMap<String, (Instant, PlayResult)>,
+}
+
+impl ResultCache {
+	/// Returns the result stored for the hash, unless it's older than `ttl` at `now`
+	pub fn get(&mut self, hash: &str, ttl: Duration, now: Instant) -> Option<PlayResult> {
+		self.entries
+			.retain(|_, (stored, _)| now.saturating_duration_since(*stored) < ttl);
+		self.entries.get(hash).map(|(_, result)| result.clone())
+	}
+
+	/// Stores the result for the hash, as obtained at `now`
+	pub fn insert(&mut self, hash: String, result: PlayResult, now: Instant) {
+		if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&hash) {
+			let oldest = self
+				.entries
+				.iter()
+				.min_by_key(|(_, (stored, _))| *stored)
+				.map(|(hash, _)| hash.clone());
+			if let Some(oldest) = oldest {
+				self.entries.remove(&oldest);
+			}
+		}
+		self.entries.insert(hash, (now, result));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn result(stdout: &str) -> PlayResult {
+		PlayResult {
+			success: true,
+			stdout: stdout.to_owned(),
+			stderr: String::new(),
+			exit_detail: String::new(),
+		}
+	}
+
+	#[test]
+	fn results_expire_after_the_ttl() {
+		let mut cache = ResultCache::default();
+		let now = Instant::now();
+		cache.insert("abc".to_owned(), result("hello"), now);
+		let ttl = Duration::from_mins(1);
+		assert_eq!(cache.get("abc", ttl, now).unwrap().stdout, "hello");
+		assert!(cache.get("def", ttl, now).is_none());
+
+		assert!(cache.get("abc", ttl, now + ttl / 2).is_some());
+		assert!(cache.get("abc", ttl, now + ttl * 2).is_none());
+		assert!(cache.entries.is_empty());
+	}
+
+	#[test]
+	fn oldest_result_is_dropped_when_full() {
+		let mut cache = ResultCache::default();
+		let now = Instant::now();
+		for i in 0..MAX_ENTRIES {
+			// All but `42` are stored a second later
+			let stored = if i == 42 {
+				now
+			} else {
+				now + Duration::from_secs(1)
+			};
+			cache.insert(i.to_string(), result(""), stored);
+		}
+		let later = now + Duration::from_secs(2);
+		cache.insert("new".to_owned(), result(""), later);
+		assert_eq!(cache.entries.len(), MAX_ENTRIES);
+		assert!(!cache.entries.contains_key("42"));
+
+		// Replacing a result doesn't need to make room
+		cache.insert("new".to_owned(), result("again"), later);
+		assert_eq!(cache.entries.len(), MAX_ENTRIES);
+	}
+}
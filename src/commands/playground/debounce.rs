@@ -0,0 +1,130 @@
+//! Guards against rapid message edits making the bot rerun the same code over and over
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use poise::serenity_prelude as serenity;
+
+use crate::types::Context;
+
+/// Edits are only tracked for this long by the framework, so older runs can be forgotten
+const TRACKING_WINDOW: Duration = Duration::from_mins(5);
+
+#[derive(Debug, Clone, Copy)]
+struct Run {
+	started: Instant,
+	finished: bool,
+}
+
+/// The latest run triggered by each message
+#[derive(Debug, Default)]
+pub struct EditDebounce {
+	runs: HashMap<serenity::MessageId, Run>,
+}
+
+impl EditDebounce {
+	/// Records a run for the message starting at `now` and returns `true`, unless the previous run
+	/// for it is still going or started less than `cooldown` before
+	pub fn try_start(
+		&mut self,
+		message: serenity::MessageId,
+		cooldown: Duration,
+		now: Instant,
+	) -> bool {
+		self.runs
+			.retain(|_, run| now.saturating_duration_since(run.started) <= TRACKING_WINDOW);
+
+		if let Some(run) = self.runs.get(&message) {
+			if !run.finished || now.saturating_duration_since(run.started) < cooldown {
+				return false;
+			}
+		}
+		self.runs.insert(
+			message,
+			Run {
+				started: now,
+				finished: false,
+			},
+		);
+		true
+	}
+
+	pub fn finish(&mut self, message: serenity::MessageId) {
+		if let Some(run) = self.runs.get_mut(&message) {
+			run.finished = true;
+		}
+	}
+}
+
+/// Marks the run as finished when dropped, so it's also marked if the command fails
+pub struct RunGuard<'a> {
+	/// `None` for slash commands, which can't be edited and thus aren't tracked
+	run: Option<(&'a Mutex<EditDebounce>, serenity::MessageId)>,
+}
+
+impl Drop for RunGuard<'_> {
+	fn drop(&mut self) {
+		if let Some((debounce, message)) = self.run {
+			debounce.lock().unwrap().finish(message);
+		}
+	}
+}
+
+/// Starts tracking a run of the command. Returns `None` if the invoking message was edited too
+/// quickly after the last run, in which case the command shouldn't run
+pub fn start_run(ctx: Context<'_>) -> Option<RunGuard<'_>> {
+	let Context::Prefix(prefix_ctx) = ctx else {
+		return Some(RunGuard { run: None });
+	};
+
+	let data = ctx.data();
+	let message = prefix_ctx.msg.id;
+	let cooldown = data.config.playground_edit_cooldown;
+	if !data
+		.playground_edit_runs
+		.lock()
+		.unwrap()
+		.try_start(message, cooldown, Instant::now())
+	{
+		return None;
+	}
+	Some(RunGuard {
+		run: Some((&data.playground_edit_runs, message)),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn runs_still_going_are_not_restarted() {
+		let mut debounce = EditDebounce::default();
+		let message = serenity::MessageId::new(1);
+		let now = Instant::now();
+		assert!(debounce.try_start(message, Duration::ZERO, now));
+		assert!(!debounce.try_start(message, Duration::ZERO, now));
+		debounce.finish(message);
+		assert!(debounce.try_start(message, Duration::ZERO, now));
+	}
+
+	#[test]
+	fn finished_runs_are_restarted_after_the_cooldown() {
+		let mut debounce = EditDebounce::default();
+		let message = serenity::MessageId::new(1);
+		let (cooldown, now) = (Duration::from_mins(1), Instant::now());
+		assert!(debounce.try_start(message, cooldown, now));
+		debounce.finish(message);
+		assert!(!debounce.try_start(message, cooldown, now + cooldown / 2));
+		assert!(debounce.try_start(message, cooldown, now + cooldown * 2));
+	}
+
+	#[test]
+	fn other_messages_are_not_held_back() {
+		let mut debounce = EditDebounce::default();
+		let (cooldown, now) = (Duration::from_mins(1), Instant::now());
+		assert!(debounce.try_start(serenity::MessageId::new(1), cooldown, now));
+		assert!(debounce.try_start(serenity::MessageId::new(2), cooldown, now));
+	}
+}
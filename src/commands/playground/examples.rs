@@ -0,0 +1,161 @@
+//! Curated example programs for demos and onboarding, run by name with `?example`
+
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+	pub name: &'static str,
+	pub description: &'static str,
+	pub code: &'static str,
+	/// Flags the example is run with, as if given to `?play`
+	pub flags: &'static [(&'static str, &'static str)],
+}
+
+impl Example {
+	/// All examples, in the order they're listed by `?example`
+	pub const ALL: &'static [Example] = &[
+		Example {
+			name: "hello_world",
+			description: "The classic first program",
+			code: r#"fn main() {
+    println!("Hello, world!");
+}"#,
+			flags: &[],
+		},
+		Example {
+			name: "ownership",
+			description: "Moving and borrowing values",
+			code: r#"fn print_len(s: &String) {
+    println!("{s:?} has {} bytes", s.len());
+}
+
+fn consume(s: String) {
+    println!("consumed {s:?}");
+}
+
+fn main() {
+    let s = String::from("hello");
+    print_len(&s); // borrowed, so `s` can still be used
+    consume(s); // moved, so `s` can't be used anymore
+}"#,
+			flags: &[],
+		},
+		Example {
+			name: "iterators",
+			description: "Chaining iterator adapters",
+			code: r#"fn main() {
+    let squares_of_evens: Vec<u32> = (1..=10)
+        .filter(|n| n % 2 == 0)
+        .map(|n| n * n)
+        .collect();
+    println!("{squares_of_evens:?}");
+    println!("sum: {}", squares_of_evens.iter().sum::<u32>());
+}"#,
+			flags: &[],
+		},
+		Example {
+			name: "error_handling",
+			description: "Propagating errors with `?`",
+			code: r#"use std::num::ParseIntError;
+
+fn double(input: &str) -> Result<i32, ParseIntError> {
+    let n: i32 = input.trim().parse()?;
+    Ok(n * 2)
+}
+
+fn main() {
+    for input in ["21", "abc"] {
+        match double(input) {
+            Ok(n) => println!("{input} doubled is {n}"),
+            Err(e) => println!("{input} isn't a number: {e}"),
+        }
+    }
+}"#,
+			flags: &[],
+		},
+		Example {
+			name: "threads",
+			description: "Sharing state between threads",
+			code: r#"use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn main() {
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || *counter.lock().unwrap() += 1)
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    println!("count: {}", counter.lock().unwrap());
+}"#,
+			flags: &[],
+		},
+		Example {
+			name: "async",
+			description: "Running futures concurrently with tokio",
+			code: r#"use std::time::Duration;
+
+async fn work(id: u32) -> u32 {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    id * 10
+}
+
+let (a, b) = tokio::join!(work(1), work(2));
+println!("{a} {b}");"#,
+			flags: &[("async", "true")],
+		},
+	];
+
+	pub fn find(name: &str) -> Option<&'static Example> {
+		Example::ALL.iter().find(|example| example.name == name)
+	}
+}
+
+/// One line per example with its name and description
+pub fn list_examples() -> String {
+	let mut list = String::new();
+	for example in Example::ALL {
+		let _ = writeln!(list, "- `{}`: {}", example.name, example.description);
+	}
+	list
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn examples_are_found_by_exact_name() {
+		assert_eq!(Example::find("iterators").unwrap().name, "iterators");
+		assert_eq!(Example::find("async").unwrap().flags, [("async", "true")]);
+		assert_eq!(Example::find("Iterators"), None);
+		assert_eq!(Example::find("iter"), None);
+	}
+
+	#[test]
+	fn example_names_are_unique() {
+		for (i, example) in Example::ALL.iter().enumerate() {
+			assert!(
+				Example::ALL[..i]
+					.iter()
+					.all(|other| other.name != example.name),
+				"{} is listed twice",
+				example.name
+			);
+		}
+	}
+
+	#[test]
+	fn every_example_is_listed_in_order() {
+		let list = list_examples();
+		assert_eq!(list.lines().count(), Example::ALL.len());
+		assert!(list.starts_with("- `hello_world`: The classic first program\n"));
+		for (line, example) in list.lines().zip(Example::ALL) {
+			assert!(line.starts_with(&format!("- `{}`: ", example.name)));
+		}
+	}
+}
@@ -0,0 +1,187 @@
+use std::fmt::Write as _;
+
+use anyhow::{bail, Error};
+
+use crate::helpers::CodeBlock;
+use crate::types::Context;
+
+/// List the items defined in a piece of code
+///
+/// Parses the code and lists its functions, types, traits, impls and modules, giving an overview \
+/// of larger snippets. Nothing is compiled or run.
+/// ```
+/// ?funcs ``​`
+/// struct Foo;
+/// impl Foo {
+///     fn new() -> Self { Foo }
+/// }
+/// ``​`
+/// ```
+#[poise::command(prefix_command, track_edits, category = "Playground")]
+pub async fn funcs(ctx: Context<'_>, code: CodeBlock) -> Result<(), Error> {
+	let outline = syn::parse_file(&code.code).map(|file| outline_items(&file.items));
+	super::util::forget_parsed_sources();
+	let outline = match outline {
+		Ok(outline) => outline,
+		Err(e) => bail!("couldn't parse the code: {e}"),
+	};
+	if outline.is_empty() {
+		ctx.say("No items found").await?;
+		return Ok(());
+	}
+
+	crate::helpers::reply_potentially_long_text(ctx, &format!("```rust\n{outline}"), "```", async {
+		String::from("(output truncated)")
+	})
+	.await
+}
+
+/// Lists the given items, one per line, with the contents of impls, traits and inline modules
+/// indented below them
+fn outline_items(items: &[syn::Item]) -> String {
+	let mut outline = String::new();
+	write_items(&mut outline, items, 0);
+	outline
+}
+
+fn write_items(outline: &mut String, items: &[syn::Item], depth: usize) {
+	let indent = "    ".repeat(depth);
+	for item in items {
+		match item {
+			syn::Item::Fn(item) => {
+				let _ = writeln!(outline, "{indent}fn {}", item.sig.ident);
+			}
+			syn::Item::Struct(item) => {
+				let _ = writeln!(outline, "{indent}struct {}", item.ident);
+			}
+			syn::Item::Enum(item) => {
+				let _ = writeln!(outline, "{indent}enum {}", item.ident);
+			}
+			syn::Item::Union(item) => {
+				let _ = writeln!(outline, "{indent}union {}", item.ident);
+			}
+			syn::Item::Type(item) => {
+				let _ = writeln!(outline, "{indent}type {}", item.ident);
+			}
+			syn::Item::Const(item) => {
+				let _ = writeln!(outline, "{indent}const {}", item.ident);
+			}
+			syn::Item::Static(item) => {
+				let _ = writeln!(outline, "{indent}static {}", item.ident);
+			}
+			syn::Item::Macro(item) => {
+				if let Some(ident) = &item.ident {
+					let _ = writeln!(outline, "{indent}macro_rules! {ident}");
+				}
+			}
+			syn::Item::Trait(item) => {
+				let _ = writeln!(outline, "{indent}trait {}", item.ident);
+				for item in &item.items {
+					if let syn::TraitItem::Fn(function) = item {
+						let _ = writeln!(outline, "{indent}    fn {}", function.sig.ident);
+					}
+				}
+			}
+			syn::Item::Impl(item) => {
+				let self_ty = type_name(&item.self_ty);
+				match &item.trait_ {
+					Some((_, trait_, _)) => {
+						let trait_ = path_name(trait_);
+						let _ = writeln!(outline, "{indent}impl {trait_} for {self_ty}");
+					}
+					None => {
+						let _ = writeln!(outline, "{indent}impl {self_ty}");
+					}
+				}
+				for item in &item.items {
+					if let syn::ImplItem::Fn(function) = item {
+						let _ = writeln!(outline, "{indent}    fn {}", function.sig.ident);
+					}
+				}
+			}
+			syn::Item::Mod(item) => {
+				let _ = writeln!(outline, "{indent}mod {}", item.ident);
+				if let Some((_, items)) = &item.content {
+					write_items(outline, items, depth + 1);
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Short, human readable name of a type, good enough to tell impl blocks apart
+fn type_name(ty: &syn::Type) -> String {
+	match ty {
+		syn::Type::Path(ty) => path_name(&ty.path),
+		syn::Type::Reference(ty) => format!("&{}", type_name(&ty.elem)),
+		syn::Type::Slice(ty) => format!("[{}]", type_name(&ty.elem)),
+		syn::Type::Tuple(ty) if ty.elems.is_empty() => "()".to_owned(),
+		_ => "_".to_owned(),
+	}
+}
+
+fn path_name(path: &syn::Path) -> String {
+	path.segments
+		.iter()
+		.map(|segment| segment.ident.to_string())
+		.collect::<Vec<_>>()
+		.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn outline(code: &str) -> String {
+		outline_items(&syn::parse_file(code).unwrap().items)
+	}
+
+	#[test]
+	fn items_are_outlined_with_their_contents_indented() {
+		let code = "
+			use std::fmt;
+			struct Foo;
+			impl Foo {
+				fn new() -> Self { Foo }
+				const ZERO: u8 = 0;
+			}
+			impl fmt::Display for Foo {
+				fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+			}
+			trait Greet {
+				fn greet(&self);
+			}
+			mod inner {
+				enum Bar {}
+				macro_rules! baz { () => {} }
+			}
+			fn main() {
+				fn nested() {}
+			}
+		";
+		assert_eq!(
+			outline(code),
+			"struct Foo\n\
+			impl Foo\n    fn new\n\
+			impl fmt::Display for Foo\n    fn fmt\n\
+			trait Greet\n    fn greet\n\
+			mod inner\n    enum Bar\n    macro_rules! baz\n\
+			fn main\n"
+		);
+	}
+
+	#[test]
+	fn impl_targets_are_named_readably() {
+		let code = "impl Trait for &[u8] {} impl Trait for () {} impl Trait for (u8, u8) {}";
+		assert_eq!(
+			outline(code),
+			"impl Trait for &[u8]\nimpl Trait for ()\nimpl Trait for _\n"
+		);
+	}
+
+	#[test]
+	fn code_without_items_has_an_empty_outline() {
+		assert_eq!(outline("use std::fmt; // fn commented_out() {}"), "");
+	}
+}
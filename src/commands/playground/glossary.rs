@@ -0,0 +1,87 @@
+//! Plain-English explanations of common runtime errors, for `explainoutput=true`
+
+/// Substrings of panic and error messages, and what they mean
+const GLOSSARY: &[(&str, &str)] = &[
+	(
+		"already borrowed: BorrowMutError",
+		"A `RefCell` was borrowed mutably while another borrow of it was still alive. `RefCell` \
+		checks the borrowing rules at runtime and panics when they're broken",
+	),
+	(
+		"already mutably borrowed: BorrowError",
+		"A `RefCell` was borrowed while a mutable borrow of it was still alive. `RefCell` checks \
+		the borrowing rules at runtime and panics when they're broken",
+	),
+	(
+		"called `Option::unwrap()` on a `None` value",
+		"`.unwrap()` was called on an `Option` that was `None`. Use `match`, `if let` or `?` to \
+		handle the missing value instead",
+	),
+	(
+		"called `Result::unwrap()` on an `Err` value",
+		"`.unwrap()` was called on a `Result` that was an error. Use `match` or `?` to handle \
+		the error instead",
+	),
+	(
+		"index out of bounds",
+		"A slice, array or `Vec` was indexed past its end. Valid indices go from 0 to the length \
+		minus one. `.get(i)` returns `None` instead of panicking",
+	),
+	(
+		"with overflow",
+		"An arithmetic operation produced a value that doesn't fit into its integer type. Debug \
+		builds check for this, release builds silently wrap around. Methods like \
+		`checked_add` or `wrapping_add` make the intended behavior explicit",
+	),
+	(
+		"attempt to divide by zero",
+		"An integer was divided by zero, which has no meaningful result",
+	),
+	(
+		"is not a char boundary",
+		"A string was sliced in the middle of a multi-byte character. String indices are byte \
+		offsets, and non-ASCII characters take up more than one byte",
+	),
+	(
+		"has overflowed its stack",
+		"The program used up its stack, usually due to recursion that's too deep or never ends",
+	),
+	(
+		"PoisonError",
+		"A `Mutex` or `RwLock` was locked after another thread panicked while holding it",
+	),
+];
+
+/// Explanations for each known error that occurs in the output, in glossary order
+pub fn explain_output(output: &str) -> Vec<&'static str> {
+	GLOSSARY
+		.iter()
+		.filter(|(pattern, _)| output.contains(pattern))
+		.map(|&(_, explanation)| explanation)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn panic_messages_are_explained() {
+		let output = "thread 'main' panicked at src/main.rs:3:13:\n\
+			called `Option::unwrap()` on a `None` value";
+		assert_eq!(explain_output(output), [GLOSSARY[2].1]);
+
+		let output = "thread 'main' panicked at src/main.rs:4:5:\n\
+			attempt to add with overflow\n\
+			thread 'main' panicked at src/main.rs:5:5:\n\
+			index out of bounds: the len is 3 but the index is 3";
+		// In glossary order, not in the order they occur
+		assert_eq!(explain_output(output), [GLOSSARY[4].1, GLOSSARY[5].1]);
+	}
+
+	#[test]
+	fn unknown_output_is_not_explained() {
+		assert!(explain_output("Hello, world!").is_empty());
+		assert!(explain_output("thread 'main' panicked at src/main.rs:2:5:\noops").is_empty());
+	}
+}
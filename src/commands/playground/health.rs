@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Error;
+
+use crate::types::Context;
+
+/// How far back `?playhealth` looks
+const HEALTH_WINDOW: Duration = Duration::from_mins(15);
+
+/// Rolling window of the outcomes of the bot's recent requests to the playground
+#[derive(Debug, Default)]
+pub struct PlaygroundHealth {
+	outcomes: VecDeque<(Instant, bool)>,
+}
+
+impl PlaygroundHealth {
+	pub fn record(&mut self, success: bool) {
+		let now = Instant::now();
+		self.forget_before(now);
+		self.outcomes.push_back((now, success));
+	}
+
+	/// Returns the number of successful and failed requests in the window
+	pub fn summary(&mut self) -> (usize, usize) {
+		self.forget_before(Instant::now());
+		let successes = self.outcomes.iter().filter(|(_, success)| *success).count();
+		(successes, self.outcomes.len() - successes)
+	}
+
+	fn forget_before(&mut self, now: Instant) {
+		while let Some(&(time, _)) = self.outcomes.front() {
+			if now.saturating_duration_since(time) <= HEALTH_WINDOW {
+				break;
+			}
+			self.outcomes.pop_front();
+		}
+	}
+}
+
+/// Show how reliably the playground answered recently
+#[poise::command(prefix_command, slash_command, category = "Playground")]
+pub async fn playhealth(ctx: Context<'_>) -> Result<(), Error> {
+	let (successes, failures) = ctx.data().playground_health.lock().unwrap().summary();
+	let minutes = HEALTH_WINDOW.as_secs() / 60;
+
+	let reply = match successes + failures {
+		0 => format!("No playground requests in the last {minutes} minutes."),
+		total => format!(
+			"Last {minutes} minutes: {total} playground requests, {successes} succeeded, \
+			{failures} failed ({}% success rate)",
+			successes * 100 / total
+		),
+	};
+
+	ctx.say(reply).await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn outcomes_are_counted() {
+		let mut health = PlaygroundHealth::default();
+		assert_eq!(health.summary(), (0, 0));
+		health.record(true);
+		health.record(false);
+		health.record(true);
+		assert_eq!(health.summary(), (2, 1));
+	}
+
+	#[test]
+	fn outcomes_outside_the_window_are_forgotten() {
+		let mut health = PlaygroundHealth::default();
+		health.record(false);
+		health.forget_before(Instant::now() + HEALTH_WINDOW / 2);
+		assert_eq!(health.outcomes.len(), 1);
+		health.forget_before(Instant::now() + HEALTH_WINDOW * 2);
+		assert!(health.outcomes.is_empty());
+	}
+}
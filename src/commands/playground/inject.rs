@@ -0,0 +1,614 @@
+//! Helpers that inject boilerplate into the user's code for the various convenience flags
+
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use anyhow::{bail, Error};
+use proc_macro2::{Span, TokenTree};
+use syn::spanned::Spanned as _;
+
+use super::api::Edition;
+use super::util::{forget_parsed_sources, hoise_crate_attributes, tokenize};
+
+/// Common std items beginners tend to use without importing them
+const COMMON_IMPORTS: &[(&str, &str)] = &[
+	("HashMap", "std::collections::HashMap"),
+	("HashSet", "std::collections::HashSet"),
+	("BTreeMap", "std::collections::BTreeMap"),
+	("BTreeSet", "std::collections::BTreeSet"),
+	("VecDeque", "std::collections::VecDeque"),
+	("BinaryHeap", "std::collections::BinaryHeap"),
+	("Rc", "std::rc::Rc"),
+	("Arc", "std::sync::Arc"),
+	("Mutex", "std::sync::Mutex"),
+	("RwLock", "std::sync::RwLock"),
+	("Cell", "std::cell::Cell"),
+	("RefCell", "std::cell::RefCell"),
+	("Duration", "std::time::Duration"),
+	("Instant", "std::time::Instant"),
+];
+
+/// Identifiers and punctuation of the code, with brackets left out. Strings and comments aren't
+/// tokens, so what they mention is ignored
+fn flat_tokens(code: &str) -> Option<Vec<TokenTree>> {
+	fn flatten(stream: proc_macro2::TokenStream, tokens: &mut Vec<TokenTree>) {
+		for token in stream {
+			match token {
+				TokenTree::Group(group) => flatten(group.stream(), tokens),
+				TokenTree::Literal(_) => {}
+				token @ (TokenTree::Ident(_) | TokenTree::Punct(_)) => tokens.push(token),
+			}
+		}
+	}
+
+	let mut tokens = Vec::new();
+	flatten(tokenize(code)?, &mut tokens);
+	Some(tokens)
+}
+
+fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+	matches!(token, Some(TokenTree::Ident(ident)) if ident == name)
+}
+
+fn is_punct(token: Option<&TokenTree>, punct: char) -> bool {
+	matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == punct)
+}
+
+/// Whether `ident` is used without being part of a path like `std::rc::Rc`
+fn uses_unqualified(tokens: &[TokenTree], ident: &str) -> bool {
+	(0..tokens.len()).any(|i| {
+		let qualified =
+			i >= 2 && is_punct(tokens.get(i - 1), ':') && is_punct(tokens.get(i - 2), ':');
+		is_ident(tokens.get(i), ident) && !qualified
+	})
+}
+
+/// Whether `ident` is already brought into scope by a `use` declaration, e.g. `use std::rc::Rc;`,
+/// `use std::rc::{Rc, Weak};` or a glob import, or defined by the code
+fn is_declared(tokens: &[TokenTree], ident: &str) -> bool {
+	let mut in_use = false;
+	(0..tokens.len()).any(|i| {
+		let token = tokens.get(i);
+		if is_ident(token, "use") {
+			in_use = true;
+		} else if is_punct(token, ';') {
+			in_use = false;
+		}
+
+		if in_use {
+			// The last segment of a path is what's imported, unless it's renamed
+			let next = tokens.get(i + 1);
+			let imported = is_ident(token, ident) && !is_punct(next, ':') && !is_ident(next, "as");
+			is_punct(token, '*') || imported
+		} else {
+			let is_definition = ["struct", "enum", "type", "trait"]
+				.iter()
+				.any(|keyword| is_ident(token, keyword));
+			is_definition && is_ident(tokens.get(i + 1), ident)
+		}
+	})
+}
+
+/// Adds `use` statements for common std items the code refers to but doesn't import
+pub fn add_common_imports(code: &str) -> String {
+	let Some(tokens) = flat_tokens(code) else {
+		return code.to_owned();
+	};
+	let added = COMMON_IMPORTS
+		.iter()
+		.filter(|(ident, _)| uses_unqualified(&tokens, ident) && !is_declared(&tokens, ident))
+		.map(|&(_, path)| path)
+		.collect::<Vec<_>>();
+	if added.is_empty() {
+		return code.to_owned();
+	}
+
+	let mut imports = String::new();
+	for path in &added {
+		imports += "use ";
+		imports += path;
+		imports += ";\n";
+	}
+	hoise_crate_attributes(code, &imports, "")
+}
+
+/// Defines `seeded_rng()`, returning a `rand::rngs::StdRng` seeded with `seed`, for reproducible
+/// randomness. `thread_rng()` can't be seeded from the outside, so the code has to opt into this
+/// one instead
+pub fn with_seeded_rng(code: &str, seed: u64) -> String {
+	let definition = format!(
+		"#[allow(dead_code)]
+fn seeded_rng() -> rand::rngs::StdRng {{
+	<rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64({seed})
+}}
+"
+	);
+	hoise_crate_attributes(code, &definition, "")
+}
+
+/// Defines `NUM_CPUS` as `cpus`. The playground doesn't let us choose how many cores
+/// `std::thread::available_parallelism()` reports, so the code has to opt into this constant
+pub fn with_num_cpus(code: &str, cpus: usize) -> String {
+	hoise_crate_attributes(
+		code,
+		&format!("#[allow(dead_code)]\nconst NUM_CPUS: usize = {cpus};\n"),
+		"",
+	)
+}
+
+/// Enables the lints which warn about code that would break when moving to the `target`
+/// edition, like `cargo fix --edition` does. They only fire when compiling with the edition right
+/// before `target`.
+pub fn with_migration_lints(code: &str, target: Edition) -> Result<String, Error> {
+	let lint_group = match target {
+		Edition::E2015 => bail!("there are no migration lints for edition 2015"),
+		Edition::E2018 => "rust_2018_compatibility",
+		Edition::E2021 => "rust_2021_compatibility",
+		Edition::E2024 => "rust_2024_compatibility",
+	};
+	Ok(hoise_crate_attributes(
+		code,
+		&format!("#![warn({lint_group})]\n"),
+		"",
+	))
+}
+
+/// Turns all warnings into errors, so the code only runs if it compiles cleanly. Lints the code
+/// allows stay allowed
+pub fn with_denied_warnings(code: &str) -> String {
+	hoise_crate_attributes(code, "#![deny(warnings)]\n", "")
+}
+
+/// Parses the code and lets `inspect` look at it, including where its tokens are
+fn inspect_file<T>(code: &str, inspect: impl FnOnce(&syn::File) -> Option<T>) -> Option<T> {
+	let inspected = syn::parse_file(code).ok().and_then(|file| inspect(&file));
+	forget_parsed_sources();
+	inspected
+}
+
+/// Where the parts of the code's `fn main` are that the flags below rewrite
+struct MainPosition {
+	/// Byte range of the name
+	name: Range<usize>,
+	/// Right after the closing paren of the parameters
+	params_end: usize,
+	/// Right after the opening brace of the body
+	body_start: usize,
+}
+
+/// Finds the code's `fn main`, if the code parses and its `fn main` is `acceptable`
+fn find_main(code: &str, acceptable: impl Fn(&syn::ItemFn) -> bool) -> Option<MainPosition> {
+	inspect_file(code, |file| {
+		file.items.iter().find_map(|item| match item {
+			syn::Item::Fn(main) if main.sig.ident == "main" && acceptable(main) => {
+				Some(MainPosition {
+					name: main.sig.ident.span().byte_range(),
+					params_end: main.sig.paren_token.span.close().byte_range().end,
+					body_start: main.block.brace_token.span.open().byte_range().end,
+				})
+			}
+			_ => None,
+		})
+	})
+}
+
+/// Runs the code's `fn main` on a thread with a stack of `stack_size_mib` MiB, for deeply
+/// recursive programs that would overflow the main thread's stack
+///
+/// The user's main function is renamed and called from a generated `fn main`, so its body and
+/// return type stay untouched.
+pub fn with_stack_size(code: &str, stack_size_mib: usize) -> Result<String, Error> {
+	let Some(main) = find_main(code, |_| true) else {
+		bail!("`stacksize` needs code with a `fn main` that parses");
+	};
+
+	let mut wrapped = code.to_owned();
+	wrapped.replace_range(main.name, "__user_main");
+	let _ = write!(
+		wrapped,
+		"\nfn main() -> std::process::ExitCode {{
+	std::thread::Builder::new()
+		.stack_size({stack_size_mib} * 1024 * 1024)
+		.spawn(|| std::process::Termination::report(__user_main()))
+		.expect(\"failed to spawn a thread with the requested stack size\")
+		.join()
+		.unwrap_or(std::process::ExitCode::from(101))
+}}
+"
+	);
+	Ok(wrapped)
+}
+
+/// Counts the heap allocations made by the code's `fn main` with a `#[global_allocator]`, and
+/// prints how many there were once it returns
+pub fn with_allocation_counter(code: &str) -> Result<String, Error> {
+	if code.contains("global_allocator") {
+		bail!("`allocs` can't be used with code that defines its own `#[global_allocator]`");
+	}
+	let Some(main) = find_main(code, |_| true) else {
+		bail!("`allocs` needs code with a `fn main` that parses");
+	};
+
+	let mut counted = code.to_owned();
+	counted.replace_range(main.name, "__uncounted_main");
+	counted += "
+static __ALLOCATIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+struct __CountingAllocator;
+
+unsafe impl std::alloc::GlobalAlloc for __CountingAllocator {
+	unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+		__ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		unsafe { std::alloc::GlobalAlloc::alloc(&std::alloc::System, layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+		unsafe { std::alloc::GlobalAlloc::dealloc(&std::alloc::System, ptr, layout) }
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+		__ALLOCATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		unsafe { std::alloc::GlobalAlloc::realloc(&std::alloc::System, ptr, layout, new_size) }
+	}
+}
+
+#[global_allocator]
+static __COUNTING_ALLOCATOR: __CountingAllocator = __CountingAllocator;
+
+fn main() -> std::process::ExitCode {
+	// The runtime allocates a bit before `main`, which isn't the code's doing
+	let before = __ALLOCATIONS.load(std::sync::atomic::Ordering::Relaxed);
+	let exit_code = std::process::Termination::report(__uncounted_main());
+	let allocations = __ALLOCATIONS.load(std::sync::atomic::Ordering::Relaxed) - before;
+	println!(\"{allocations} heap allocations (including reallocations)\");
+	exit_code
+}
+";
+	Ok(counted)
+}
+
+/// Sets a panic hook at the start of `fn main` which prints just the panic message to stdout,
+/// instead of the usual message with location and backtrace hint on stderr. Expects code that
+/// was wrapped in a `fn main` already, if needed
+pub fn with_pretty_panics(code: &str) -> Result<String, Error> {
+	let Some(main) = find_main(code, |_| true) else {
+		bail!("`prettypanic` needs code with a `fn main` that parses");
+	};
+
+	let mut hooked = code.to_owned();
+	hooked.insert_str(
+		main.body_start,
+		"
+	std::panic::set_hook(Box::new(|info| {
+		let payload = info.payload();
+		let message = payload
+			.downcast_ref::<&str>()
+			.copied()
+			.or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+			.unwrap_or(\"unknown reason\");
+		println!(\"The program panicked: {message}\");
+	}));
+",
+	);
+	Ok(hooked)
+}
+
+/// Prints the value of the expression `fn main` ends in, for `value=true`
+///
+/// The user's main function is renamed and made to return `impl Debug`, so its body stays
+/// untouched. Only a plain `fn main()` is supported.
+pub fn with_last_value_printed(code: &str) -> Result<String, Error> {
+	let ends_in_value = |main: &syn::ItemFn| {
+		main.sig.asyncness.is_none()
+			&& matches!(main.sig.output, syn::ReturnType::Default)
+			&& matches!(
+				main.block.stmts.last(),
+				Some(syn::Stmt::Expr(expr, None))
+					if !matches!(expr, syn::Expr::ForLoop(_) | syn::Expr::While(_))
+			)
+	};
+	let Some(main) = find_main(code, ends_in_value) else {
+		bail!("`value` needs a `fn main()` that parses and ends in an expression");
+	};
+
+	let mut printed = code.to_owned();
+	// The name comes before the parameters, so inserting after them doesn't move it
+	printed.insert_str(main.params_end, " -> impl std::fmt::Debug");
+	printed.replace_range(main.name, "__value_main");
+	printed += "\nfn main() {\n\tprintln!(\"{:?}\", __value_main());\n}\n";
+	Ok(printed)
+}
+
+/// Whether the program died from a stack overflow
+pub fn is_stack_overflow(stderr: &str) -> bool {
+	stderr.contains("has overflowed its stack")
+}
+
+/// Whether one of the attributes is a `#[derive]` with `Debug` in it, however it's spelled
+fn derives_debug(attrs: &[syn::Attribute]) -> bool {
+	attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("derive"))
+		.any(|attr| {
+			let mut debug = false;
+			let _ = attr.parse_nested_meta(|meta| {
+				debug |= meta
+					.path
+					.segments
+					.last()
+					.is_some_and(|last| last.ident == "Debug");
+				Ok(())
+			});
+			debug
+		})
+}
+
+/// Collects where struct and enum definitions without `#[derive(Debug)]` start, after their
+/// attributes
+struct MissingDebug(Vec<usize>);
+
+impl MissingDebug {
+	fn check(&mut self, attrs: &[syn::Attribute], vis: &syn::Visibility, keyword: Span) {
+		if !derives_debug(attrs) {
+			let start = match vis {
+				syn::Visibility::Inherited => keyword,
+				vis => vis.span(),
+			};
+			self.0.push(start.byte_range().start);
+		}
+	}
+}
+
+impl<'ast> syn::visit::Visit<'ast> for MissingDebug {
+	fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+		self.check(&item.attrs, &item.vis, item.struct_token.span);
+		syn::visit::visit_item_struct(self, item);
+	}
+
+	fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+		self.check(&item.attrs, &item.vis, item.enum_token.span);
+		syn::visit::visit_item_enum(self, item);
+	}
+}
+
+/// Adds `#[derive(Debug)]` to struct and enum definitions which don't derive it yet, so that
+/// `?eval` can print values of them. Code that doesn't parse is left as is
+pub fn derive_debug(code: &str) -> String {
+	// `?eval` code is the body of `fn main` rather than a whole file
+	const BODY_PREFIX: &str = "fn main() {";
+
+	let missing = |file: &syn::File| {
+		let mut missing = MissingDebug(Vec::new());
+		syn::visit::visit_file(&mut missing, file);
+		Some(missing.0)
+	};
+	let positions = inspect_file(code, missing).or_else(|| {
+		let positions = inspect_file(&format!("{BODY_PREFIX}{code}\n}}"), missing)?;
+		Some(
+			positions
+				.iter()
+				.map(|pos| pos - BODY_PREFIX.len())
+				.collect(),
+		)
+	});
+
+	let mut derived = code.to_owned();
+	for pos in positions.unwrap_or_default().into_iter().rev() {
+		let line_start = code[..pos].rfind('\n').map_or(0, |newline| newline + 1);
+		let indentation = &code[line_start..pos];
+		if indentation.trim().is_empty() {
+			derived.insert_str(pos, &format!("#[derive(Debug)]\n{indentation}"));
+		} else {
+			derived.insert_str(pos, "#[derive(Debug)] ");
+		}
+	}
+	derived
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn common_imports_are_added_when_missing() {
+		let code = "fn main() {\n\tlet map: HashMap<u8, u8> = HashMap::new();\n}\n";
+		let imported = add_common_imports(code);
+		assert_eq!(
+			imported.matches("use std::collections::HashMap;").count(),
+			1
+		);
+		assert!(imported.ends_with(code));
+	}
+
+	#[test]
+	fn existing_imports_are_not_added_twice() {
+		for import in [
+			"use std::collections::HashMap;",
+			"use std::collections::{HashMap, HashSet};",
+			"use std::collections::{HashSet, HashMap};",
+			"use std::collections::*;",
+			"pub use std::collections::HashMap;",
+		] {
+			let code = format!("{import}\nfn main() {{\n\tlet _ = HashMap::<u8, u8>::new();\n}}\n");
+			assert_eq!(add_common_imports(&code), code, "{import}");
+		}
+	}
+
+	#[test]
+	fn own_definitions_and_qualified_paths_are_left_alone() {
+		let code = "struct Rc;\nfn main() {\n\tlet _ = Rc;\n\tlet _ = std::sync::Arc::new(1);\n}\n";
+		assert_eq!(add_common_imports(code), code);
+	}
+
+	#[test]
+	fn renamed_imports_still_need_the_original_name() {
+		let code =
+			"use std::rc::Rc as Shared;\nfn main() {\n\tlet _ = Rc::new(Shared::new(1));\n}\n";
+		assert!(add_common_imports(code).contains("use std::rc::Rc;\n"));
+	}
+
+	#[test]
+	fn strings_and_comments_are_ignored() {
+		let code = "fn main() {\n\t// a HashMap would be faster\n\tprintln!(\"Rc\");\n}\n";
+		assert_eq!(add_common_imports(code), code);
+
+		let code = "// struct HashMap;\nfn main() {\n\tlet _ = HashMap::<u8, u8>::new();\n}\n";
+		assert!(add_common_imports(code).starts_with("use std::collections::HashMap;\n"));
+	}
+
+	#[test]
+	fn main_runs_on_a_thread_with_the_requested_stack() {
+		let code = "fn main() -> Result<(), String> {\n\tOk(())\n}\n";
+		let wrapped = with_stack_size(code, 64).unwrap();
+		assert!(wrapped.starts_with("fn __user_main() -> Result<(), String> {"));
+		assert!(wrapped.contains(".stack_size(64 * 1024 * 1024)"));
+		syn::parse_file(&wrapped).unwrap();
+
+		assert!(with_stack_size("fn not_main() {}", 64).is_err());
+		assert!(with_stack_size("fn main() {", 64).is_err());
+	}
+
+	#[test]
+	fn main_mentioned_in_comments_and_strings_is_left_alone() {
+		let code = "// fn main() {\nconst S: &str = \"fn main(\";\nfn main() {}\n";
+		let wrapped = with_stack_size(code, 8).unwrap();
+		assert!(wrapped
+			.starts_with("// fn main() {\nconst S: &str = \"fn main(\";\nfn __user_main() {}\n"));
+		syn::parse_file(&wrapped).unwrap();
+	}
+
+	#[test]
+	fn stack_overflows_are_recognized() {
+		assert!(is_stack_overflow(
+			"\nthread 'main' has overflowed its stack\nfatal runtime error: stack overflow\n"
+		));
+		assert!(!is_stack_overflow(
+			"thread 'main' panicked at src/main.rs:2:5"
+		));
+	}
+
+	#[test]
+	fn migration_lints_of_the_target_edition_are_enabled() {
+		let code = with_migration_lints("#![allow(unused)]\nfn main() {}", Edition::E2021).unwrap();
+		assert!(code.starts_with("#![allow(unused)]\n#![warn(rust_2021_compatibility)]\n"));
+		assert!(with_migration_lints("fn main() {}", Edition::E2015).is_err());
+	}
+
+	#[test]
+	fn debug_is_derived_where_missing() {
+		let code = "struct A;
+#[derive(Clone, Debug)]
+pub struct B;
+#[derive(Clone)]
+// a comment
+pub(crate) enum C {}
+fn main() {
+	struct D(u8);
+}
+";
+		assert_eq!(
+			derive_debug(code),
+			"#[derive(Debug)]
+struct A;
+#[derive(Clone, Debug)]
+pub struct B;
+#[derive(Clone)]
+// a comment
+#[derive(Debug)]
+pub(crate) enum C {}
+fn main() {
+	#[derive(Debug)]
+	struct D(u8);
+}
+"
+		);
+	}
+
+	#[test]
+	fn debug_derives_are_recognized_however_spelled() {
+		let code =
+			"#[derive(\n\tClone,\n\tDebug,\n)]\nstruct A;\n#[derive(std::fmt::Debug)]\nenum B {}\n";
+		assert_eq!(derive_debug(code), code);
+	}
+
+	#[test]
+	fn definitions_in_strings_and_comments_are_left_alone() {
+		let code = "// struct A;\nfn main() {\n\tlet _ = \"struct B;\";\n}\n";
+		assert_eq!(derive_debug(code), code);
+	}
+
+	#[test]
+	fn debug_is_derived_in_eval_code() {
+		assert_eq!(
+			derive_debug("struct A(u8);\nA(1)"),
+			"#[derive(Debug)]\nstruct A(u8);\nA(1)"
+		);
+		assert_eq!(derive_debug("struct A {"), "struct A {");
+	}
+
+	#[test]
+	fn seeded_rng_is_defined_after_crate_attributes() {
+		let code = with_seeded_rng("#![allow(unused)]\nfn main() {}\n", 42);
+		assert!(code.starts_with("#![allow(unused)]\n#[allow(dead_code)]\nfn seeded_rng()"));
+		assert!(code.contains("seed_from_u64(42)"));
+		syn::parse_file(&code).unwrap();
+	}
+
+	#[test]
+	fn num_cpus_is_defined() {
+		let code = with_num_cpus("fn main() {}\n", 4);
+		assert!(code.starts_with("#[allow(dead_code)]\nconst NUM_CPUS: usize = 4;\n"));
+		syn::parse_file(&code).unwrap();
+	}
+
+	#[test]
+	fn panic_hook_is_set_at_the_start_of_main() {
+		let code =
+			with_pretty_panics("fn helper() {}\nfn main() {\n\tpanic!(\"oh no\");\n}\n").unwrap();
+		assert!(code.starts_with("fn helper() {}\nfn main() {\n\tstd::panic::set_hook("));
+		assert!(code.ends_with("\tpanic!(\"oh no\");\n}\n"));
+		syn::parse_file(&code).unwrap();
+
+		assert!(with_pretty_panics("panic!(\"no main\");").is_err());
+	}
+
+	#[test]
+	fn panic_hook_is_not_set_in_a_commented_out_main() {
+		let code = "// fn main() { old version }\nfn main() {\n\tpanic!();\n}\n";
+		let hooked = with_pretty_panics(code).unwrap();
+		assert!(hooked
+			.starts_with("// fn main() { old version }\nfn main() {\n\tstd::panic::set_hook("));
+		syn::parse_file(&hooked).unwrap();
+	}
+
+	#[test]
+	fn allocations_of_main_are_counted() {
+		let code = with_allocation_counter("fn main() {\n\tlet v = vec![1];\n}\n").unwrap();
+		assert!(code.starts_with("fn __uncounted_main() {"));
+		assert!(code.contains("#[global_allocator]"));
+		syn::parse_file(&code).unwrap();
+
+		let own_allocator =
+			"#[global_allocator]\nstatic A: std::alloc::System = std::alloc::System;\nfn main() {}";
+		assert!(with_allocation_counter(own_allocator).is_err());
+	}
+
+	#[test]
+	fn value_of_main_is_printed() {
+		let code = with_last_value_printed("fn main() {\n\tlet x = 1;\n\tx + 1\n}\n").unwrap();
+		assert!(code.starts_with("fn __value_main() -> impl std::fmt::Debug {"));
+		syn::parse_file(&code).unwrap();
+
+		assert!(with_last_value_printed("fn main() {\n\tlet x = 1;\n}").is_err());
+		assert!(with_last_value_printed("fn main() {\n\tfor _ in 0..1 {}\n}").is_err());
+		assert!(with_last_value_printed("fn main() -> i32 {\n\t1\n}").is_err());
+	}
+
+	#[test]
+	fn warnings_are_denied_after_other_crate_attributes() {
+		assert_eq!(
+			with_denied_warnings("#![allow(dead_code)]\nfn main() {}"),
+			"#![allow(dead_code)]\n#![deny(warnings)]\nfn main() {}\n"
+		);
+	}
+}
@@ -1,12 +1,13 @@
 use anyhow::Error;
 
+use crate::helpers::CodeBlock;
 use crate::types::Context;
 
 use super::{
-	api::{CrateType, Mode, PlayResult, PlaygroundRequest},
+	api::{execute, CrateType, Mode, PlayResult, PlaygroundRequest},
 	util::{
-		format_play_eval_stderr, generic_help, hoise_crate_attributes, parse_flags, send_reply,
-		stub_message, GenericHelp,
+		format_play_eval_stderr, generic_help, hoise_crate_attributes, parse_invoker_flags,
+		send_reply, stub_message, GenericHelp,
 	},
 };
 
@@ -67,7 +68,7 @@ fn bench(functions: &[(&str, fn())]) {
 pub async fn microbench(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
 
@@ -110,23 +111,19 @@ pub async fn microbench(
 	// final assembled code
 	let code = hoise_crate_attributes(user_code, after_crate_attrs, &after_code);
 
-	let (flags, mut flag_parse_errors) = parse_flags(flags);
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/execute")
-		.json(&PlaygroundRequest {
+	let (flags, mut flag_parse_errors) = parse_invoker_flags(ctx, flags);
+	let mut result: PlayResult = execute(
+		ctx,
+		&PlaygroundRequest {
 			code: &code,
 			channel: flags.channel,
 			crate_type: CrateType::Binary,
 			edition: flags.edition,
 			mode: Mode::Release, // benchmarks on debug don't make sense
 			tests: false,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
+		},
+	)
+	.await?;
 
 	result.stderr = format_play_eval_stderr(&result.stderr, flags.warn);
 
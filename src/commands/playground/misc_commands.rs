@@ -1,63 +1,101 @@
 use std::borrow::Cow;
 
 use anyhow::Error;
+use poise::futures_util::future::join;
 use tracing::warn;
 
-use crate::types::Context;
+use crate::helpers::{diff_lines, merge_output_and_errors, CodeBlock};
+use crate::types::{Context, Data};
 
 use super::{
 	api::{
-		apply_online_rustfmt, ClippyRequest, CrateType, MacroExpansionRequest, MiriRequest,
-		PlayResult,
+		apply_online_rustfmt, execute, execute_miri, post_json, ClippyRequest, CrateType, Edition,
+		MacroExpansionRequest, MiriRequest, PlayResult, PlaygroundRequest,
 	},
 	util::{
-		extract_relevant_lines, generic_help, maybe_wrap, maybe_wrapped, parse_flags, send_reply,
-		strip_fn_main_boilerplate_from_formatted, stub_message, GenericHelp, ResultHandling,
+		defer_slash_command, extract_relevant_lines, format_miri_stderr, format_play_eval_stderr,
+		generic_help, labeled_sections, limit_diagnostics, maybe_wrap, maybe_wrapped,
+		parse_invoker_flags, send_reply, send_reply_with_footer, slash_command_flags,
+		strip_fn_main_boilerplate_from_formatted, stub_message, with_slash_command, GenericHelp,
+		ResultHandling,
 	},
 };
 
+/// Run code and detect undefined behavior using Miri
+#[must_use]
+pub fn miri() -> poise::Command<Data, Error> {
+	with_slash_command(miri_prefix(), miri_slash())
+}
+
 /// Run code and detect undefined behavior using Miri
 #[poise::command(
 	prefix_command,
+	rename = "miri",
 	track_edits,
 	help_text_fn = "miri_help",
 	category = "Playground"
 )]
-pub async fn miri(
+async fn miri_prefix(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
+	run_miri(ctx, flags, code).await
+}
+
+/// Run code and detect undefined behavior using Miri
+#[poise::command(slash_command, rename = "miri", category = "Playground")]
+async fn miri_slash(
+	ctx: Context<'_>,
+	#[description = "Code to run"] code: String,
+	#[description = "Rust edition"] edition: Option<Edition>,
+	#[description = "Only show the result to you"] ephemeral: Option<bool>,
+	#[description = "More flags, like `maxerrors=3`"] flags: Option<String>,
+) -> Result<(), Error> {
+	defer_slash_command(ctx, ephemeral == Some(true)).await?;
+	let flags = slash_command_flags(None, edition, None, None, ephemeral, flags.as_deref());
+	let code = CodeBlock {
+		code,
+		language: None,
+	};
+	run_miri(ctx, flags, code).await
+}
+
+async fn run_miri(
+	ctx: Context<'_>,
+	flags: poise::KeyValueArgs,
+	code: CodeBlock,
+) -> Result<(), Error> {
 	let code = &maybe_wrapped(
 		&code.code,
 		ResultHandling::Discard,
 		ctx.prefix().contains("Sweat"),
 		false,
 	);
-	let (flags, flag_parse_errors) = parse_flags(flags);
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
 
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/miri")
-		.json(&MiriRequest {
+	let mut result = execute_miri(
+		ctx,
+		&MiriRequest {
 			code,
 			edition: flags.edition,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
-
-	result.stderr = extract_relevant_lines(
-		&result.stderr,
-		&["Running `/playground"],
-		&["error: aborting"],
+		},
 	)
-	.to_owned();
+	.await?;
 
-	send_reply(ctx, result, code, &flags, &flag_parse_errors).await
+	result.stderr = format_miri_stderr(&result.stderr);
+
+	let mut footer = String::new();
+	if let Some(maxerrors) = flags.maxerrors {
+		let (stderr, omitted) = limit_diagnostics(&result.stderr, "error", maxerrors.get());
+		result.stderr = stderr;
+		if omitted > 0 {
+			footer = format!("({omitted} more errors omitted)\n");
+		}
+	}
+
+	send_reply_with_footer(ctx, result, code, &flags, &flag_parse_errors, &footer).await
 }
 
 #[must_use]
@@ -72,7 +110,7 @@ pub fn miri_help() -> String {
 		warn: false,
 		run: false,
 		example_code: "code",
-	})
+	}) + "- maxerrors: show only the first N errors Miri reports (default: all)\n"
 }
 
 /// Expand macros to their raw desugared form
@@ -85,26 +123,23 @@ pub fn miri_help() -> String {
 pub async fn expand(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
 
 	let code = maybe_wrap(&code.code, ResultHandling::None);
 	let was_fn_main_wrapped = matches!(code, Cow::Owned(_));
-	let (flags, flag_parse_errors) = parse_flags(flags);
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
 
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/macro-expansion")
-		.json(&MacroExpansionRequest {
+	let mut result: PlayResult = post_json(
+		ctx,
+		"macro-expansion",
+		&MacroExpansionRequest {
 			code: &code,
 			edition: flags.edition,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
+		},
+	)
+	.await?;
 
 	result.stderr = extract_relevant_lines(
 		&result.stderr,
@@ -149,7 +184,7 @@ pub fn expand_help() -> String {
 pub async fn clippy(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
 
@@ -164,21 +199,18 @@ pub async fn clippy(
 			false,
 		)
 	);
-	let (flags, flag_parse_errors) = parse_flags(flags);
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
 
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/clippy")
-		.json(&ClippyRequest {
+	let mut result: PlayResult = post_json(
+		ctx,
+		"clippy",
+		&ClippyRequest {
 			code,
 			edition: flags.edition,
 			crate_type: CrateType::Binary,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
+		},
+	)
+	.await?;
 
 	result.stderr = extract_relevant_lines(
 		&result.stderr,
@@ -217,13 +249,13 @@ pub fn clippy_help() -> String {
 pub async fn fmt(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
 
 	let code = &maybe_wrap(&code.code, ResultHandling::None);
 	let was_fn_main_wrapped = matches!(code, Cow::Owned(_));
-	let (flags, flag_parse_errors) = parse_flags(flags);
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
 
 	let mut result = apply_online_rustfmt(ctx, code, flags.edition).await?;
 
@@ -245,3 +277,161 @@ pub fn fmt_help() -> String {
 		example_code: "code",
 	})
 }
+
+/// Format code using rustfmt, then run it
+#[poise::command(
+	prefix_command,
+	track_edits,
+	help_text_fn = "fmtrun_help",
+	category = "Playground"
+)]
+pub async fn fmtrun(
+	ctx: Context<'_>,
+	flags: poise::KeyValueArgs,
+	code: CodeBlock,
+) -> Result<(), Error> {
+	ctx.say(stub_message(ctx)).await?;
+
+	let (flags, mut flag_parse_errors) = parse_invoker_flags(ctx, flags);
+
+	let wrapped = maybe_wrap(&code.code, ResultHandling::None);
+	let rustfmt_result = apply_online_rustfmt(ctx, &wrapped, flags.edition).await?;
+	let formatted = if rustfmt_result.success {
+		match wrapped {
+			Cow::Owned(_) => strip_fn_main_boilerplate_from_formatted(&rustfmt_result.stdout),
+			Cow::Borrowed(_) => rustfmt_result.stdout,
+		}
+	} else {
+		flag_parse_errors += "rustfmt couldn't format the code, running it as-is\n";
+		code.code.clone()
+	};
+
+	let code = &maybe_wrapped(
+		&formatted,
+		ResultHandling::None,
+		ctx.prefix().contains("Sweat"),
+		false,
+	);
+	let mut result = execute(
+		ctx,
+		&PlaygroundRequest {
+			code,
+			channel: flags.channel,
+			crate_type: CrateType::Binary,
+			edition: flags.edition,
+			mode: flags.mode,
+			tests: false,
+		},
+	)
+	.await?;
+	result.stderr = format_play_eval_stderr(&result.stderr, flags.warn);
+
+	let result = PlayResult {
+		success: result.success,
+		stdout: labeled_sections([
+			("Formatted code", &*formatted),
+			(
+				"Output",
+				&*merge_output_and_errors(&result.stdout, &result.stderr),
+			),
+		]),
+		stderr: String::new(),
+		exit_detail: result.exit_detail,
+	};
+	send_reply(ctx, result, code, &flags, &flag_parse_errors).await
+}
+
+#[must_use]
+pub fn fmtrun_help() -> String {
+	generic_help(GenericHelp {
+		command: "fmtrun",
+		desc: "Format code using rustfmt, then run it and show both the formatted code and its \
+		output",
+		mode_and_channel: true,
+		warn: true,
+		run: false,
+		example_code: "code",
+	})
+}
+
+/// Show how the behavior of code differs between two editions
+#[poise::command(
+	prefix_command,
+	track_edits,
+	help_text_fn = "editiondiff_help",
+	category = "Playground"
+)]
+pub async fn editiondiff(
+	ctx: Context<'_>,
+	mut flags: poise::KeyValueArgs,
+	code: CodeBlock,
+) -> Result<(), Error> {
+	ctx.say(stub_message(ctx)).await?;
+
+	// Taken out before `parse_flags`, which would reject them
+	let old_edition = match flags.0.remove("from") {
+		Some(edition) => edition.parse()?,
+		None => Edition::E2018,
+	};
+	let new_edition = match flags.0.remove("to") {
+		Some(edition) => edition.parse()?,
+		None => Edition::E2021,
+	};
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
+
+	let code = &maybe_wrapped(
+		&code.code,
+		ResultHandling::None,
+		ctx.prefix().contains("Sweat"),
+		false,
+	);
+	let run_in = |edition| async move {
+		execute(
+			ctx,
+			&PlaygroundRequest {
+				code,
+				channel: flags.channel,
+				crate_type: CrateType::Binary,
+				edition,
+				mode: flags.mode,
+				tests: false,
+			},
+		)
+		.await
+	};
+	let (old, new) = join(run_in(old_edition), run_in(new_edition)).await;
+	let (old, new) = (old?, new?);
+
+	// Warnings are the main thing editions change, so they're always shown
+	let old_output =
+		merge_output_and_errors(&old.stdout, &format_play_eval_stderr(&old.stderr, true))
+			.into_owned();
+	let new_output =
+		merge_output_and_errors(&new.stdout, &format_play_eval_stderr(&new.stderr, true))
+			.into_owned();
+
+	let (old_name, new_name) = (old_edition.name(), new_edition.name());
+	let stdout = match diff_lines(&old_output, &new_output) {
+		Some(diff) => format!("Differences (- edition {old_name}, + edition {new_name}):\n{diff}"),
+		None => format!("Editions {old_name} and {new_name} behave the same:\n{new_output}"),
+	};
+	let result = PlayResult {
+		success: old.success && new.success,
+		stdout,
+		stderr: String::new(),
+		exit_detail: String::new(),
+	};
+	send_reply(ctx, result, code, &flags, &flag_parse_errors).await
+}
+
+#[must_use]
+pub fn editiondiff_help() -> String {
+	generic_help(GenericHelp {
+		command: "editiondiff",
+		desc: "Run code in two editions and show how their output and warnings differ",
+		mode_and_channel: true,
+		warn: false,
+		run: false,
+		example_code: "code",
+	}) + "- from: edition to compare from (default: 2018)\n- to: edition to compare to (default: 2021)\n"
+}
@@ -0,0 +1,124 @@
+//! File-backed store of the code run with `permalink=true`, so it can be retrieved with `?get`
+//! even after the playground's gists are gone
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Error};
+use poise::serenity_prelude as serenity;
+
+use crate::types::Context;
+
+/// One file per snippet, named after its content hash, in the configured directory
+#[derive(Debug, Clone)]
+pub struct PermalinkStore {
+	dir: PathBuf,
+}
+
+impl PermalinkStore {
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self { dir: dir.into() }
+	}
+
+	/// Stores the code under the given hash. Storing the same hash twice overwrites the file,
+	/// which is fine because the hash is derived from the code
+	pub async fn store(&self, hash: &str, code: &str) -> Result<(), Error> {
+		let path = self.path(hash)?;
+		tokio::fs::create_dir_all(&self.dir).await?;
+		tokio::fs::write(path, code).await?;
+		Ok(())
+	}
+
+	/// Returns the code stored under the given hash, or `None` if there's none
+	pub async fn load(&self, hash: &str) -> Result<Option<String>, Error> {
+		match tokio::fs::read_to_string(self.path(hash)?).await {
+			Ok(code) => Ok(Some(code)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Hashes come from users in `?get`, so only hex digits may end up in the file name
+	fn path(&self, hash: &str) -> Result<PathBuf, Error> {
+		if hash.is_empty() || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+			bail!("invalid hash `{}`, expected hex digits", hash);
+		}
+		Ok(self.dir.join(format!("{}.rs", hash.to_ascii_lowercase())))
+	}
+}
+
+/// Retrieve code that was run with `permalink=true`
+#[poise::command(prefix_command, slash_command, category = "Playground")]
+pub async fn get(
+	ctx: Context<'_>,
+	#[description = "Hash shown below the result"] hash: String,
+) -> Result<(), Error> {
+	let Some(dir) = &ctx.data().config.playground_permalink_dir else {
+		ctx.say("Permalinks aren't enabled on this bot.").await?;
+		return Ok(());
+	};
+
+	let Some(code) = PermalinkStore::new(dir).load(&hash).await? else {
+		ctx.say(format!("No code stored under `{hash}`.")).await?;
+		return Ok(());
+	};
+
+	let reply = format!("```rust\n{}\n```", code.trim_end());
+	if reply.chars().count() <= 2000 {
+		ctx.say(reply).await?;
+	} else {
+		ctx.send(
+			poise::CreateReply::default()
+				.content("Code too large for a message:")
+				.attachment(serenity::CreateAttachment::bytes(
+					code,
+					format!("{hash}.rs"),
+				)),
+		)
+		.await?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_store(name: &str) -> PermalinkStore {
+		let dir = std::env::temp_dir().join(format!(
+			"ferrisbot-permalinks-{name}-{}",
+			std::process::id()
+		));
+		let _ = std::fs::remove_dir_all(&dir);
+		PermalinkStore::new(dir)
+	}
+
+	#[tokio::test]
+	async fn stored_code_can_be_loaded() {
+		let store = temp_store("roundtrip");
+		store.store("00ff12", "fn main() {}").await.unwrap();
+		assert_eq!(
+			store.load("00FF12").await.unwrap().as_deref(),
+			Some("fn main() {}")
+		);
+		assert_eq!(store.load("abcdef").await.unwrap(), None);
+		let _ = std::fs::remove_dir_all(&store.dir);
+	}
+
+	#[tokio::test]
+	async fn hashes_must_be_hex() {
+		let store = temp_store("invalid");
+		for hash in ["", "../secret", "12 34", "xyz"] {
+			assert!(store.store(hash, "").await.is_err(), "{hash}");
+			assert!(store.load(hash).await.is_err(), "{hash}");
+		}
+	}
+
+	#[tokio::test]
+	async fn write_errors_are_reported() {
+		// A file where the directory should be makes creating the directory fail
+		let store = temp_store("unwritable");
+		std::fs::write(&store.dir, "").unwrap();
+		assert!(store.store("abc", "fn main() {}").await.is_err());
+		let _ = std::fs::remove_file(&store.dir);
+	}
+}
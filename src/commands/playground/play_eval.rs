@@ -1,74 +1,1025 @@
-use anyhow::Error;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::time::Duration;
 
-use crate::types::Context;
+use anyhow::{bail, Error};
+use poise::futures_util::future::{join, join_all};
+use tracing::warn;
+
+use crate::helpers::{diff_lines, merge_output_and_errors, CodeBlock};
+use crate::types::{Context, Data};
 
 use super::{
-	api::{CrateType, PlayResult, PlaygroundRequest},
+	api::{
+		apply_online_rustfmt, compile_to_target, compile_to_target_in_mode, execute, execute_miri,
+		fetch_crates, post_json, post_webhook, Channel, ClippyRequest, CodegenBackend,
+		CommandFlags, CompileResponse, CompileTarget, CrateType, Edition, MiriRequest, Mode,
+		PlayResult, PlaygroundRequest, WebhookPayload, BOOL_FLAGS, NUMBER_FLAGS,
+	},
+	debounce,
+	examples::{list_examples, Example},
+	glossary::explain_output,
+	inject,
+	permalink::PermalinkStore,
 	util::{
-		format_play_eval_stderr, generic_help, maybe_wrapped, parse_flags, send_reply,
-		stub_message, GenericHelp, ResultHandling,
+		apply_inline_suggestions, await_with_deadline, cargo_manifest, count_instructions,
+		count_unsafe, dedupe_lines, defer_slash_command, describe_mode_divergence, describe_panic,
+		describe_size_reduction, describe_wasm_size, find_blocking_in_async, find_forbidden_crates,
+		format_miri_stderr, format_play_eval_stderr, generic_help, group_warnings_by_lint,
+		has_clippy_diagnostics, is_internal_compiler_error, labeled_sections,
+		limit_compiler_warnings, line_length_histogram, maybe_wrap, maybe_wrapped, outcome_matrix,
+		parse_invoker_flags, send_diff_reply, send_issue_reply, send_reply_with_footer,
+		slash_command_flags, split_flag_directive, strip_fn_main_boilerplate_from_formatted,
+		stub_message, style_summary, uses_network, with_slash_command, GenericHelp, ResultHandling,
 	},
 };
 
+/// Help for the flags only understood by `?play` and its siblings. There are too many to fit
+/// into their help text, so they're listed by `?playflags` instead. Only the flags that aren't in
+/// [`BOOL_FLAGS`] or [`NUMBER_FLAGS`] are written out here, see [`play_flags_help`]
+const PLAY_FLAGS_HELP: &str = "\
+Flags understood by `?play`, `?playwarn` and `?eval`, in addition to the ones listed in their \
+help. Flags can also be put into the first line of the code, like `//! play: mode=release`; \
+flags given to the command take precedence.
+- template: allow-unused, tokio-main (default: none)
+- async: true, false. Shorthand for `template=tokio-main` (default: false)
+- editions: comma separated list of editions, like `2018,2021`. Run the code on each and show \
+which ones it compiles and runs on (default: none)
+- migrate: 2018, 2021, 2024. Warn about code that breaks in that edition. Compile with the \
+edition before it (default: none)
+- forbid: comma separated crates. Refuse to run code that uses any of them via `use`, \
+`extern crate` or a path like `rand::random()` (default: none)
+- seed: define `seeded_rng()`, returning a `rand::rngs::StdRng` seeded with N. Use it instead \
+of `thread_rng()`, which can't be seeded (default: none)
+- rustflags: comma separated rustc flags. Only `-C opt-level=0|3` (same as `mode=debug|release`) \
+can be applied by the playground; `opt-level=1|2|s|z`, `target-cpu=native`, \
+`debug-assertions=on|off` and `overflow-checks=on|off` are accepted but only work in `?godbolt`. \
+Anything else is rejected (default: none)
+- caplints: allow, warn, deny. Would pass `--cap-lints`, which limits lint levels even over \
+`deny`/`forbid`, unlike `warn=true` or `#![warn(...)]` which set them. The playground doesn't \
+support it, so this points you to `?godbolt` instead (default: none)
+- backend: llvm, cranelift. The playground only compiles with LLVM, so `cranelift` is \
+rejected (default: llvm)
+- deadline: after N seconds (at most 30), change the placeholder to say the code is still \
+running (default: none)
+- miriseeds: run Miri with N different seeds. Not possible on the playground, so this \
+only shows how to do it locally
+";
+
+/// [`PLAY_FLAGS_HELP`], followed by the flags from the flag tables
+fn play_flags_help() -> String {
+	let mut help = PLAY_FLAGS_HELP.to_owned();
+	for flag in BOOL_FLAGS {
+		if let Some(desc) = flag.help {
+			let _ = writeln!(
+				help,
+				"- {}: true, false. {desc} (default: false)",
+				flag.name
+			);
+		}
+	}
+	for flag in NUMBER_FLAGS {
+		if let Some(desc) = flag.help {
+			let _ = writeln!(help, "- {}: {desc}", flag.name);
+		}
+	}
+	help
+}
+
+/// Pointer to `?playflags`, appended to the help text of the commands understanding those flags
+const MORE_FLAGS_HELP: &str = "- and many more, see `?playflags`\n";
+
+/// Longer `deadline=N` values are capped to this, as the playground gives up on its own anyway
+const MAX_DEADLINE_SECS: u64 = 30;
+
 // play and eval work similarly, so this function abstracts over the two
 async fn play_or_eval(
 	ctx: Context<'_>,
-	flags: poise::KeyValueArgs,
+	mut flags: poise::KeyValueArgs,
 	force_warnings: bool, // If true, force enable warnings regardless of flags
-	code: poise::CodeBlock,
-	result_handling: ResultHandling,
+	code: CodeBlock,
+	mut result_handling: ResultHandling,
 ) -> Result<(), Error> {
-	ctx.say(stub_message(ctx)).await?;
+	// Rapid edits shouldn't make us run the code over and over. The previous result stays up, as
+	// replying would replace it
+	let Some(_run) = debounce::start_run(ctx) else {
+		return crate::helpers::acknowledge_success(ctx, "rustWait", '⏳').await;
+	};
+
+	// Slash commands are deferred instead
+	let stub = match ctx {
+		Context::Prefix(_) => Some(ctx.say(stub_message(ctx)).await?),
+		Context::Application(_) => None,
+	};
+
+	// Flags given to the command take precedence over the ones in the code
+	let (code, directive_errors) = split_flag_directive(&code.code, &mut flags);
+	// `style=true` and `unsafecheck=true` judge the code as written, before `prepare_code`
+	// transforms it
+	let source = code.to_owned();
+
+	let (mut flags, mut flag_parse_errors) = parse_invoker_flags(ctx, flags);
+	flag_parse_errors += &directive_errors;
+	if flags.ephemeral && matches!(ctx, Context::Prefix(_)) {
+		flag_parse_errors += "`ephemeral` only works with slash commands\n";
+	}
+	if flags.webhook && ctx.data().config.playground_webhook_url.is_none() {
+		flag_parse_errors += "`webhook` isn't set up on this bot\n";
+	}
+
+	if flags.showtype && matches!(result_handling, ResultHandling::Print) {
+		result_handling = ResultHandling::PrintWithType;
+	}
 
-	let code = maybe_wrapped(
-		&code.code,
+	let unsf = ctx.prefix().contains("Sweat");
+	let pretty = ctx.prefix().contains("OwO") || ctx.prefix().contains("Cat");
+	let code = prepare_code(
+		source.clone(),
+		&mut flags,
 		result_handling,
-		ctx.prefix().contains("Sweat"),
-		ctx.prefix().contains("OwO") || ctx.prefix().contains("Cat"),
-	);
-	let (mut flags, flag_parse_errors) = parse_flags(flags);
+		unsf,
+		pretty,
+		&mut flag_parse_errors,
+	)?;
 
-	if force_warnings {
+	if force_warnings || flags.groupwarn {
 		flags.warn = true;
 	}
 
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/execute")
-		.json(&PlaygroundRequest {
-			code: &code,
-			channel: flags.channel,
-			crate_type: CrateType::Binary,
-			edition: flags.edition,
-			mode: flags.mode,
-			tests: false,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
+	// Taken before the flags below adjust the settings, which they'd do again when replayed
+	let replay = flags.replay.then(|| {
+		let command = match result_handling {
+			ResultHandling::Print | ResultHandling::PrintWithType => "eval",
+			ResultHandling::None | ResultHandling::Discard => "play",
+		};
+		let code_source = match ctx {
+			Context::Prefix(prefix_ctx) => format!(" with the code of {}", prefix_ctx.msg.link()),
+			Context::Application(_) => String::new(),
+		};
+		format!(
+			"replay: `{}{command} {}`{code_source}\n",
+			ctx.data().config.prefix,
+			flags.replay_args()
+		)
+	});
+
+	if send_comparison(ctx, &code, &flags, &flag_parse_errors).await? {
+		return Ok(());
+	}
+
+	let mut request = PlaygroundRequest {
+		code: &code,
+		channel: flags.channel,
+		crate_type: CrateType::Binary,
+		edition: flags.edition,
+		mode: flags.mode,
+		tests: false,
+	};
+
+	let start_time = std::time::Instant::now();
+	let (mut result, cached) = execute_cached(ctx, &request, &flags, stub.as_ref()).await?;
+	let elapsed = start_time.elapsed();
+
+	if flags.icefallback
+		&& matches!(flags.channel, Channel::Nightly)
+		&& is_internal_compiler_error(&result.stderr)
+	{
+		flags.channel = Channel::Stable;
+		request.channel = Channel::Stable;
+		result = execute(ctx, &request).await?;
+		flag_parse_errors += "Nightly hit an internal compiler error; here's the stable result\n";
+	}
+
+	// Taken before `withtests` modifies the request
+	let content_hash = (flags.hash || flags.permalink).then(|| request.content_hash());
+
+	let omitted_warnings = format_output(&mut result, &flags, result_handling);
+	let mut result = with_extra_runs(ctx, result, request, &flags).await?;
+
+	let mut footer = result_notes(omitted_warnings, cached);
+	footer += &compare_with_earlier_runs(ctx, &mut result, &code, &flags).await?;
+	footer += &describe_output(&mut result, &flags, elapsed);
+	if let Some(replay) = &replay {
+		footer += replay;
+	}
+	footer += &describe_code(ctx, &source, &code, &flags).await;
+	if let Some(content_hash) = &content_hash {
+		if flags.hash {
+			let _ = writeln!(footer, "hash: {content_hash}");
+		}
+		if flags.permalink {
+			footer += &store_permalink(ctx, content_hash, &code).await;
+		}
+	}
+
+	if flags.webhook {
+		post_result_to_webhook(ctx, &code, &flags, &result).await;
+	}
+
+	if flags.issue {
+		return send_issue_reply(ctx, &result, &code, &flags, &flag_parse_errors).await;
+	}
+
+	let nothing_to_show = result.stdout.trim().is_empty()
+		&& result.stderr.trim().is_empty()
+		&& footer.is_empty()
+		&& flag_parse_errors.is_empty();
+	if flags.silent && result.success && nothing_to_show {
+		if let Some(stub) = stub {
+			// Errors are ignored in case the placeholder was deleted already
+			let _ = stub.delete(ctx).await;
+		}
+		return crate::helpers::acknowledge_success(ctx, "rustOk", '✅').await;
+	}
+
+	send_reply_with_footer(ctx, result, &code, &flags, &flag_parse_errors, &footer).await
+}
+
+/// Applies the flags that change the code before it's run, like `autouse` or `template`, and
+/// wraps it in `fn main` if needed, like [`maybe_wrapped`]. Problems that don't stop the run are
+/// added to `notes`
+fn prepare_code(
+	code: String,
+	flags: &mut CommandFlags,
+	result_handling: ResultHandling,
+	unsf: bool,
+	pretty: bool,
+	notes: &mut String,
+) -> Result<String, Error> {
+	// Only a warning, in case the code merely mentions the types without connecting anywhere
+	if flags.netcheck && uses_network(&code) {
+		*notes += "Network access is blocked on the playground, so this will likely fail\n";
+	}
+
+	if flags.asynccheck {
+		for call in find_blocking_in_async(&code) {
+			let _ = writeln!(
+				notes,
+				"Blocking call `{call}` in async context may stall the runtime"
+			);
+		}
+	}
+
+	if flags.backend == CodegenBackend::Cranelift {
+		if !matches!(flags.channel, Channel::Nightly) {
+			bail!("`backend=cranelift` needs `channel=nightly`");
+		}
+		bail!("The playground only compiles with LLVM, so `backend=cranelift` can't be honored");
+	}
+
+	let forbidden = find_forbidden_crates(&code, &flags.forbid.0);
+	if !forbidden.is_empty() {
+		bail!(
+			"The code uses `{}`, which `forbid` doesn't allow",
+			forbidden.join("`, `")
+		);
+	}
+
+	let code = if flags.autouse {
+		inject::add_common_imports(&code)
+	} else {
+		code
+	};
+
+	let code = if flags.autoderive {
+		inject::derive_debug(&code)
+	} else {
+		code
+	};
+
+	let code = match flags.seed {
+		Some(seed) => {
+			if code.contains("thread_rng") {
+				*notes +=
+					"`thread_rng()` can't be seeded, use `seeded_rng()` for reproducible output\n";
+			}
+			inject::with_seeded_rng(&code, seed)
+		}
+		None => code,
+	};
+
+	let code = match flags.cpus {
+		Some(cpus) => {
+			if code.contains("available_parallelism") {
+				*notes += "`available_parallelism()` still reports the playground's cores, use \
+					`NUM_CPUS` instead\n";
+			}
+			inject::with_num_cpus(&code, cpus.get())
+		}
+		None => code,
+	};
 
+	let code = match flags.migrate {
+		Some(target) => {
+			if flags.edition >= target {
+				let _ = writeln!(
+					notes,
+					"migration lints for {} only fire with an older `edition`",
+					target.name()
+				);
+			}
+			flags.warn = true;
+			inject::with_migration_lints(&code, target)?
+		}
+		None => code,
+	};
+
+	let code = if flags.denywarnings {
+		if flags.migrate.is_some() {
+			*notes += "with `denywarnings=true`, migration lints fail the build too\n";
+		}
+		inject::with_denied_warnings(&code)
+	} else {
+		code
+	};
+
+	let mut code = match flags.template {
+		Some(template) => template.apply(&code, result_handling, unsf, pretty)?,
+		None => maybe_wrapped(&code, result_handling, unsf, pretty).into_owned(),
+	};
+
+	if flags.value {
+		code = inject::with_last_value_printed(&code)?;
+	}
+	if let Some(stack_size) = flags.stacksize {
+		code = inject::with_stack_size(&code, stack_size.get())?;
+	}
+	if flags.allocs {
+		code = inject::with_allocation_counter(&code)?;
+	}
+	if flags.prettypanic {
+		code = inject::with_pretty_panics(&code)?;
+	}
+	Ok(code)
+}
+
+/// Handles the flags that run the code several times and reply with a comparison instead of the
+/// normal result: `editions`, `allchannels`, `panicdemo` and `optmir`. Returns whether one of
+/// them was given and replied to
+async fn send_comparison(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &CommandFlags,
+	notes: &str,
+) -> Result<bool, Error> {
+	let request = |channel, edition, mode| PlaygroundRequest {
+		code,
+		channel,
+		crate_type: CrateType::Binary,
+		edition,
+		mode,
+		tests: false,
+	};
+
+	if let Some(editions) = &flags.editions {
+		let runs = editions
+			.0
+			.iter()
+			.map(|&edition| (edition.name(), request(flags.channel, edition, flags.mode)));
+		send_outcome_matrix(ctx, runs.collect(), code, flags, notes).await?;
+		return Ok(true);
+	}
+
+	if flags.allchannels {
+		let channels = [Channel::Stable, Channel::Beta, Channel::Nightly];
+		let runs =
+			channels.map(|channel| (channel.name(), request(channel, flags.edition, flags.mode)));
+		send_outcome_matrix(ctx, runs.into(), code, flags, notes).await?;
+		return Ok(true);
+	}
+
+	if flags.panicdemo {
+		let (debug, release) = join(
+			execute(ctx, &request(flags.channel, flags.edition, Mode::Debug)),
+			execute(ctx, &request(flags.channel, flags.edition, Mode::Release)),
+		)
+		.await;
+		let (mut debug, mut release) = (debug?, release?);
+		debug.stderr = format_play_eval_stderr(&debug.stderr, false);
+		release.stderr = format_play_eval_stderr(&release.stderr, false);
+
+		let summary = describe_mode_divergence(&debug, &release);
+		let sections = labeled_sections([
+			(
+				"debug",
+				&*merge_output_and_errors(&debug.stdout, &debug.stderr),
+			),
+			(
+				"release",
+				&*merge_output_and_errors(&release.stdout, &release.stderr),
+			),
+		]);
+		let result = PlayResult {
+			success: debug.success && release.success,
+			stdout: sections,
+			stderr: String::new(),
+			exit_detail: String::new(),
+		};
+		send_reply_with_footer(ctx, result, code, flags, notes, &summary).await?;
+		return Ok(true);
+	}
+
+	if flags.optmir {
+		let (debug, release) = join(
+			compile_to_target_in_mode(ctx, code, flags, CompileTarget::Mir, Mode::Debug),
+			compile_to_target_in_mode(ctx, code, flags, CompileTarget::Mir, Mode::Release),
+		)
+		.await;
+		let (debug, release) = (debug?, release?);
+		if let Some(failed) = [&debug, &release].into_iter().find(|mir| !mir.success) {
+			let result = PlayResult {
+				success: false,
+				stdout: String::new(),
+				stderr: format_play_eval_stderr(&failed.stderr, flags.warn),
+				exit_detail: String::new(),
+			};
+			send_reply_with_footer(ctx, result, code, flags, notes, "").await?;
+			return Ok(true);
+		}
+
+		let diff = match diff_lines(&debug.code, &release.code) {
+			Some(diff) => format!("--- debug\n+++ release\n{diff}"),
+			None => "MIR is the same in debug and release mode\n".to_owned(),
+		};
+		send_diff_reply(ctx, &diff, code, flags, notes).await?;
+		return Ok(true);
+	}
+
+	Ok(false)
+}
+
+/// Runs each of the labeled requests at once and replies with which ones succeeded, for
+/// `editions` and `allchannels`
+async fn send_outcome_matrix(
+	ctx: Context<'_>,
+	runs: Vec<(&'static str, PlaygroundRequest<'_>)>,
+	code: &str,
+	flags: &CommandFlags,
+	notes: &str,
+) -> Result<(), Error> {
+	let results = join_all(runs.iter().map(|(_, request)| execute(ctx, request))).await;
+
+	let mut rows = Vec::new();
+	for ((label, _), result) in runs.iter().zip(results) {
+		let mut result = result?;
+		result.stderr = format_play_eval_stderr(&result.stderr, false);
+		rows.push((*label, result));
+	}
+
+	let result = PlayResult {
+		success: rows.iter().all(|(_, result)| result.success),
+		stdout: outcome_matrix(&rows),
+		stderr: String::new(),
+		exit_detail: String::new(),
+	};
+	send_reply_with_footer(ctx, result, code, flags, notes, "").await
+}
+
+/// Runs the request, unless an identical one ran recently enough to reuse its result. Returns
+/// the result and whether it came from the cache. With `deadline`, the placeholder message is
+/// updated when the code takes long
+async fn execute_cached(
+	ctx: Context<'_>,
+	request: &PlaygroundRequest<'_>,
+	flags: &CommandFlags,
+	stub: Option<&poise::ReplyHandle<'_>>,
+) -> Result<(PlayResult, bool), Error> {
+	let cache_ttl = ctx.data().config.playground_result_cache_ttl;
+	let cache_key = request.content_hash();
+	// `nocache=true` still stores the fresh result, so later runs get that one
+	if !cache_ttl.is_zero() && !flags.nocache {
+		let cached_result = ctx.data().playground_results.lock().unwrap().get(
+			&cache_key,
+			cache_ttl,
+			std::time::Instant::now(),
+		);
+		if let Some(result) = cached_result {
+			return Ok((result, true));
+		}
+	}
+
+	let result = match (flags.deadline, stub) {
+		(Some(deadline), Some(stub)) => {
+			let deadline = Duration::from_secs(deadline.get().min(MAX_DEADLINE_SECS));
+			let update_stub = async {
+				// Errors are ignored in case the placeholder was deleted
+				let _ = stub
+					.edit(
+						ctx,
+						poise::CreateReply::default()
+							.content("_Still running, will update when done..._"),
+					)
+					.await;
+			};
+			await_with_deadline(execute(ctx, request), deadline, update_stub).await?
+		}
+		_ => execute(ctx, request).await?,
+	};
+	if !cache_ttl.is_zero() {
+		ctx.data().playground_results.lock().unwrap().insert(
+			cache_key,
+			result.clone(),
+			std::time::Instant::now(),
+		);
+	}
+	Ok((result, false))
+}
+
+/// Footer lines about how the result was obtained, i.e. whether warnings were left out or it
+/// came from the cache
+fn result_notes(omitted_warnings: usize, cached: bool) -> String {
+	let mut notes = String::new();
+	if omitted_warnings > 0 {
+		let _ = writeln!(notes, "({omitted_warnings} more warnings omitted)");
+	}
+	if cached {
+		// Nightly may have changed since, or the program may print something different each run
+		notes += "(cached, rerun with `nocache=true` for a fresh result)\n";
+	}
+	notes
+}
+
+/// Cleans up the output of a normal run: trims and formats stderr, and applies `maxwarn`,
+/// `groupwarn` and `dedupe`. Returns how many warnings `maxwarn` left out
+fn format_output(
+	result: &mut PlayResult,
+	flags: &CommandFlags,
+	result_handling: ResultHandling,
+) -> usize {
+	let mut omitted_warnings = 0;
+	if let Some(maxwarn) = flags.maxwarn {
+		(result.stderr, omitted_warnings) = limit_compiler_warnings(&result.stderr, maxwarn.get());
+	}
 	result.stderr = format_play_eval_stderr(&result.stderr, flags.warn);
+	if flags.groupwarn {
+		if let Some(summary) = group_warnings_by_lint(&result.stderr) {
+			result.stderr = summary + "\n" + &result.stderr;
+		}
+	}
+
+	// A panicking expression leaves stdout empty, so make sure the reason is front and center
+	if !matches!(result_handling, ResultHandling::None) && result.stdout.trim().is_empty() {
+		if let Some(stderr) = describe_panic(&result.stderr, "The expression") {
+			result.stderr = stderr;
+		}
+	}
+
+	if flags.dedupe {
+		result.stdout = dedupe_lines(&result.stdout);
+	}
+	omitted_warnings
+}
+
+/// Adds the output of the other runs `withtests` and `safety` ask for to the result
+async fn with_extra_runs(
+	ctx: Context<'_>,
+	mut result: PlayResult,
+	mut request: PlaygroundRequest<'_>,
+	flags: &CommandFlags,
+) -> Result<PlayResult, Error> {
+	if flags.withtests {
+		request.tests = true;
+		let mut test_result = execute(ctx, &request).await?;
+		test_result.stderr = format_play_eval_stderr(&test_result.stderr, flags.warn);
+
+		result = PlayResult {
+			success: result.success && test_result.success,
+			stdout: labeled_sections([
+				(
+					"Program output",
+					&*merge_output_and_errors(&result.stdout, &result.stderr),
+				),
+				(
+					"Test results",
+					&*merge_output_and_errors(&test_result.stdout, &test_result.stderr),
+				),
+			]),
+			stderr: String::new(),
+			exit_detail: result.exit_detail,
+		};
+	}
+
+	if flags.safety {
+		let mut miri_result = execute_miri(
+			ctx,
+			&MiriRequest {
+				code: request.code,
+				edition: flags.edition,
+			},
+		)
+		.await?;
+		miri_result.stderr = format_miri_stderr(&miri_result.stderr);
 
-	send_reply(ctx, result, &code, &flags, &flag_parse_errors).await
+		let sections = labeled_sections([
+			(
+				"Program output",
+				&*merge_output_and_errors(&result.stdout, &result.stderr),
+			),
+			(
+				"Miri",
+				&*merge_output_and_errors(&miri_result.stdout, &miri_result.stderr),
+			),
+		]);
+		let success = result.success && miri_result.success;
+		let summary = outcome_matrix(&[("run", result), ("miri", miri_result)]);
+		result = PlayResult {
+			success,
+			stdout: summary + &sections,
+			stderr: String::new(),
+			exit_detail: String::new(),
+		};
+	}
+	Ok(result)
+}
+
+/// Compares the output to the one under Miri for `miridiff` and to the previous run in the
+/// channel for `diffprev`, replacing it with the differences if there are any. Returns the
+/// footer lines for when there aren't
+async fn compare_with_earlier_runs(
+	ctx: Context<'_>,
+	result: &mut PlayResult,
+	code: &str,
+	flags: &CommandFlags,
+) -> Result<String, Error> {
+	let mut footer = String::new();
+	if flags.miridiff {
+		let mut miri_result = execute_miri(
+			ctx,
+			&MiriRequest {
+				code,
+				edition: flags.edition,
+			},
+		)
+		.await?;
+		miri_result.stderr = format_miri_stderr(&miri_result.stderr);
+
+		match diff_lines(&result.stdout, &miri_result.stdout) {
+			Some(diff) => {
+				// Miri's errors usually explain why it stopped printing early
+				let miri_errors = if miri_result.success {
+					String::new()
+				} else {
+					labeled_sections([("Miri", &*miri_result.stderr)])
+				};
+				*result = PlayResult {
+					success: false,
+					stdout: format!(
+						"stdout diverges under Miri (- run, + Miri):\n{diff}{miri_errors}"
+					),
+					stderr: String::new(),
+					exit_detail: String::new(),
+				};
+			}
+			None if miri_result.success => footer += "Miri printed the same stdout\n",
+			None => {
+				footer += "Miri printed the same stdout, but reported errors, try `safety=true`\n";
+			}
+		}
+	}
+
+	// Every run is remembered, so that the next one can be compared to it
+	let output = merge_output_and_errors(&result.stdout, &result.stderr).into_owned();
+	let previous_output = ctx
+		.data()
+		.playground_last_outputs
+		.lock()
+		.unwrap()
+		.insert(ctx.channel_id(), output.clone());
+	if flags.diffprev {
+		match previous_output.map(|previous| diff_lines(&previous, &output)) {
+			Some(Some(diff)) => {
+				result.stdout = format!(
+					"output changed since the previous run in this channel (- before, + now):\n\
+					{diff}"
+				);
+				result.stderr = String::new();
+			}
+			Some(None) => footer += "same output as the previous run in this channel\n",
+			None => footer += "no previous run in this channel to compare with\n",
+		}
+	}
+	Ok(footer)
+}
+
+/// Footer lines explaining the output, for `explainoutput`, `histogram`, `phasetime` and `clock`,
+/// plus a hint if the stack overflowed. Also applies `merge`, which has to come after the output
+/// was looked at, as it moves stderr into stdout
+fn describe_output(result: &mut PlayResult, flags: &CommandFlags, elapsed: Duration) -> String {
+	let mut footer = String::new();
+	if flags.explainoutput {
+		for explanation in explain_output(&format!("{}\n{}", result.stdout, result.stderr)) {
+			let _ = writeln!(footer, "💡 {explanation}");
+		}
+	}
+	if flags.histogram {
+		match line_length_histogram(&result.stdout) {
+			Some(histogram) => {
+				let _ = writeln!(footer, "stdout line lengths:\n```\n{histogram}```");
+			}
+			None => footer += "no stdout to make a histogram of\n",
+		}
+	}
+	if inject::is_stack_overflow(&result.stderr) && flags.stacksize.is_none() {
+		footer += "hint: the stack overflowed. Deep recursion may need a bigger stack, try \
+			`stacksize=64` to run `main` on a thread with 64 MiB of stack\n";
+	}
+	if flags.merge {
+		// The playground captures both streams separately and doesn't tell us how they were
+		// interleaved, so the best we can do is show them in a fixed order
+		let sections = [("stderr", &result.stderr), ("stdout", &result.stdout)];
+		result.stdout = labeled_sections(
+			sections
+				.into_iter()
+				.filter(|(_, text)| !text.trim().is_empty())
+				.map(|(label, text)| (label, text.as_str())),
+		);
+		result.stderr = String::new();
+		footer += "stdout and stderr are captured separately, so their relative order is lost\n";
+	}
+	if flags.phasetime || flags.clock {
+		// The playground only gives us the final result, so compile and run time can't be told
+		// apart. Say so, instead of letting people guess which one was slow
+		let _ = writeln!(
+			footer,
+			"took {:.2}s total (compile + run, measured by the bot)",
+			elapsed.as_secs_f64()
+		);
+	}
+	if flags.clock {
+		// Likewise, all output arrives at once, so there's no telling when a line was printed
+		footer += "lines can't be timestamped, the playground only returns output once the \
+			program exits\n";
+	}
+	footer
+}
+
+/// Footer lines about the code rather than its output, for `cargotoml`, `binsize`,
+/// `optcompare`, `style`, `suggest` and `unsafecheck`. `source` is the code as written, `code`
+/// the code that was run
+async fn describe_code(ctx: Context<'_>, source: &str, code: &str, flags: &CommandFlags) -> String {
+	let mut footer = String::new();
+	if flags.cargotoml {
+		match fetch_crates(ctx).await {
+			Ok(crates) => {
+				let manifest = cargo_manifest(code, flags.edition, &crates);
+				let _ = writeln!(footer, "Cargo.toml:\n```toml\n{manifest}```");
+			}
+			Err(error) => {
+				warn!("failed to fetch the playground's crates: {error:?}");
+				footer += "couldn't fetch the playground's crates to make a Cargo.toml\n";
+			}
+		}
+	}
+	if flags.binsize {
+		footer += &describe_binary_size(ctx, code, flags).await;
+	}
+	if flags.optcompare {
+		footer += &compare_opt_levels(ctx, code, flags).await;
+	}
+	if flags.style {
+		let _ = writeln!(footer, "{}", check_style(ctx, source, code, flags).await);
+	}
+	if flags.suggest {
+		footer += &suggest_rewrite(ctx, source, flags).await;
+	}
+	if flags.unsafecheck {
+		match count_unsafe(source) {
+			Some(0) => {}
+			Some(count) => {
+				let _ = writeln!(
+					footer,
+					"⚠️ contains {count} `unsafe` block(s) or item(s), consider checking the code \
+					with `?miri`"
+				);
+			}
+			None => footer += "couldn't check for `unsafe`, the code doesn't tokenize\n",
+		}
+	}
+	footer
+}
+
+/// Estimates the size of the compiled program for `binsize=true`
+async fn describe_binary_size(ctx: Context<'_>, code: &str, flags: &CommandFlags) -> String {
+	// The playground doesn't report the size of the executable it built. The wasm target comes
+	// closest, though only as the text format
+	match compile_to_target(ctx, code, flags, CompileTarget::Wasm).await {
+		Ok(CompileResponse {
+			success: true,
+			code: wat,
+			..
+		}) => describe_wasm_size(&wat) + "\n",
+		Ok(_) => "binary size unavailable: failed to compile to wasm\n".to_owned(),
+		Err(e) => {
+			warn!("Couldn't compile to wasm: {}", e);
+			"binary size unavailable\n".to_owned()
+		}
+	}
+}
+
+/// Compares the amount of assembly in debug and release mode for `optcompare=true`
+async fn compare_opt_levels(ctx: Context<'_>, code: &str, flags: &CommandFlags) -> String {
+	let (debug, release) = join(
+		compile_to_target_in_mode(ctx, code, flags, CompileTarget::Asm, Mode::Debug),
+		compile_to_target_in_mode(ctx, code, flags, CompileTarget::Asm, Mode::Release),
+	)
+	.await;
+	match (debug, release) {
+		(
+			Ok(CompileResponse {
+				success: true,
+				code: debug,
+				..
+			}),
+			Ok(CompileResponse {
+				success: true,
+				code: release,
+				..
+			}),
+		) => {
+			describe_size_reduction(count_instructions(&debug), count_instructions(&release)) + "\n"
+		}
+		(Err(e), _) | (_, Err(e)) => {
+			warn!("Couldn't compile to assembly: {}", e);
+			"assembly comparison unavailable\n".to_owned()
+		}
+		_ => "assembly comparison unavailable: failed to compile\n".to_owned(),
+	}
+}
+
+/// Runs Clippy on the code that's run and rustfmt on the code as written for `style=true`, and
+/// summarizes whether both are happy with it
+async fn check_style(ctx: Context<'_>, source: &str, code: &str, flags: &CommandFlags) -> String {
+	// Same lints as `?clippy` allows, as they're caused by the wrapping rather than the user
+	let clippy_code = format!("#![allow(dead_code, clippy::let_unit_value)] {code}");
+	let clippy_request = ClippyRequest {
+		code: &clippy_code,
+		edition: flags.edition,
+		crate_type: CrateType::Binary,
+	};
+	let wrapped_source = maybe_wrap(source, ResultHandling::None);
+	let (clippy, rustfmt) = join(
+		post_json::<PlayResult>(ctx, "clippy", &clippy_request),
+		apply_online_rustfmt(ctx, &wrapped_source, flags.edition),
+	)
+	.await;
+
+	let clippy = match clippy {
+		Ok(clippy) => clippy,
+		Err(e) => {
+			warn!("Couldn't run clippy: {}", e);
+			return "style check unavailable".to_owned();
+		}
+	};
+	let formatted = match rustfmt {
+		Ok(PlayResult {
+			success: true,
+			stdout,
+			..
+		}) => {
+			let stdout = match wrapped_source {
+				Cow::Owned(_) => strip_fn_main_boilerplate_from_formatted(&stdout),
+				Cow::Borrowed(_) => stdout,
+			};
+			Some(stdout.trim() == source.trim())
+		}
+		Ok(_) => None,
+		Err(e) => {
+			warn!("Couldn't run rustfmt: {}", e);
+			return "style check unavailable".to_owned();
+		}
+	};
+
+	style_summary(
+		clippy.success && !has_clippy_diagnostics(&clippy.stderr),
+		formatted,
+	)
+}
+
+/// Runs Clippy on the code as written for `suggest=true`, and returns footer lines with the
+/// changes its inline suggestions make. The rewritten code isn't run.
+async fn suggest_rewrite(ctx: Context<'_>, source: &str, flags: &CommandFlags) -> String {
+	// Longer rewrites would crowd out the program's output
+	const MAX_SUGGESTIONS_LENGTH: usize = 600;
+
+	let wrapped_source = maybe_wrap(source, ResultHandling::None);
+	// On a line of its own, so Clippy's line numbers are simply off by one
+	let clippy_code = format!("#![allow(dead_code, clippy::let_unit_value)]\n{wrapped_source}");
+	let clippy_request = ClippyRequest {
+		code: &clippy_code,
+		edition: flags.edition,
+		crate_type: CrateType::Binary,
+	};
+	let clippy = match post_json::<PlayResult>(ctx, "clippy", &clippy_request).await {
+		Ok(clippy) => clippy,
+		Err(e) => {
+			warn!("Couldn't run clippy: {}", e);
+			return "suggestions unavailable\n".to_owned();
+		}
+	};
+
+	let (rewritten, applied) = apply_inline_suggestions(&clippy_code, &clippy.stderr);
+	let rewritten = rewritten.split_once('\n').map_or("", |(_, code)| code);
+	let Some(diff) = diff_lines(&wrapped_source, rewritten) else {
+		return "Clippy has no suggestions that can be applied automatically\n".to_owned();
+	};
+	let changes = diff
+		.lines()
+		.filter(|line| !line.starts_with("  "))
+		.collect::<Vec<_>>()
+		.join("\n");
+	if changes.len() > MAX_SUGGESTIONS_LENGTH {
+		format!("Clippy has {applied} suggestions, too many to show here, try `?clippy`\n")
+	} else {
+		format!("Clippy suggests ({applied}):\n```diff\n{changes}\n```\n")
+	}
+}
+
+/// Stores the code for `permalink=true` and returns the footer line pointing to it. Failures are
+/// reported in the footer line instead, so they never keep the reply from being sent.
+async fn store_permalink(ctx: Context<'_>, content_hash: &str, code: &str) -> String {
+	let Some(dir) = &ctx.data().config.playground_permalink_dir else {
+		return "permalinks aren't enabled on this bot\n".to_owned();
+	};
+	match PermalinkStore::new(dir).store(content_hash, code).await {
+		Ok(()) => format!("permalink: `?get {content_hash}`\n"),
+		Err(e) => {
+			warn!("Couldn't store permalink: {}", e);
+			format!("couldn't store the permalink: {e}\n")
+		}
+	}
+}
+
+/// Posts the result to the operator's webhook for `webhook=true`, which
+/// `restrict_moderator_flags` only leaves on for moderators. Failures are only logged, so they
+/// never keep the reply from being sent.
+async fn post_result_to_webhook(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &CommandFlags,
+	result: &PlayResult,
+) {
+	let Some(url) = &ctx.data().config.playground_webhook_url else {
+		return;
+	};
+
+	let payload = WebhookPayload {
+		code,
+		channel: flags.channel,
+		mode: flags.mode,
+		edition: flags.edition,
+		success: result.success,
+		stdout: &result.stdout,
+		stderr: &result.stderr,
+	};
+	if let Err(e) = post_webhook(ctx, url, &payload).await {
+		warn!("Couldn't post playground result to webhook: {}", e);
+	}
+}
+
+/// Compile and run Rust code in a playground
+#[must_use]
+pub fn play() -> poise::Command<Data, Error> {
+	with_slash_command(play_prefix(), play_slash())
 }
 
 /// Compile and run Rust code in a playground
 #[poise::command(
 	prefix_command,
+	rename = "play",
 	track_edits,
 	help_text_fn = "play_help",
 	category = "Playground"
 )]
-pub async fn play(
+async fn play_prefix(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	play_or_eval(ctx, flags, false, code, ResultHandling::None).await
 }
 
+/// Compile and run Rust code in a playground
+#[poise::command(slash_command, rename = "play", category = "Playground")]
+#[allow(clippy::too_many_arguments)]
+async fn play_slash(
+	ctx: Context<'_>,
+	#[description = "Code to run"] code: String,
+	#[description = "Release channel"] channel: Option<Channel>,
+	#[description = "Rust edition"] edition: Option<Edition>,
+	#[description = "Compilation mode"] mode: Option<Mode>,
+	#[description = "Show compiler warnings"] warn: Option<bool>,
+	#[description = "Only show the result to you"] ephemeral: Option<bool>,
+	#[description = "More flags, like `tail=10 dedupe=true`. See ?playflags"] flags: Option<String>,
+) -> Result<(), Error> {
+	defer_slash_command(ctx, ephemeral == Some(true)).await?;
+	let flags = slash_command_flags(channel, edition, mode, warn, ephemeral, flags.as_deref());
+	let code = CodeBlock {
+		code,
+		language: None,
+	};
+	play_or_eval(ctx, flags, false, code, ResultHandling::None).await
+}
+
 #[must_use]
 pub fn play_help() -> String {
 	generic_help(GenericHelp {
@@ -78,7 +1029,7 @@ pub fn play_help() -> String {
 		warn: true,
 		run: false,
 		example_code: "code",
-	})
+	}) + MORE_FLAGS_HELP
 }
 
 /// Compile and run Rust code with warnings
@@ -91,7 +1042,7 @@ category = "Playground"
 pub async fn playwarn(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	play_or_eval(ctx, flags, true, code, ResultHandling::None).await
 }
@@ -105,24 +1056,89 @@ pub fn playwarn_help() -> String {
 		warn: false,
 		run: false,
 		example_code: "code",
-	})
+	}) + MORE_FLAGS_HELP
+}
+
+/// Run one of the bundled example programs
+///
+/// Runs the example with the given name, or lists all examples if none is given.
+/// ```
+/// ?example hello_world
+/// ```
+#[poise::command(prefix_command, track_edits, category = "Playground")]
+pub async fn example(ctx: Context<'_>, name: Option<String>) -> Result<(), Error> {
+	let Some(name) = name else {
+		ctx.say(format!("Available examples:\n{}", list_examples()))
+			.await?;
+		return Ok(());
+	};
+	let Some(example) = Example::find(&name) else {
+		ctx.say(format!(
+			"Unknown example `{name}`. Available examples:\n{}",
+			list_examples()
+		))
+		.await?;
+		return Ok(());
+	};
+
+	let flags = poise::KeyValueArgs(
+		example
+			.flags
+			.iter()
+			.map(|&(key, value)| (key.to_owned(), value.to_owned()))
+			.collect(),
+	);
+	let code = CodeBlock {
+		code: example.code.to_owned(),
+		language: Some("rust".to_owned()),
+	};
+	play_or_eval(ctx, flags, false, code, ResultHandling::None).await
+}
+
+/// Evaluate a single Rust expression
+#[must_use]
+pub fn eval() -> poise::Command<Data, Error> {
+	with_slash_command(eval_prefix(), eval_slash())
 }
 
 /// Evaluate a single Rust expression
 #[poise::command(
 	prefix_command,
+	rename = "eval",
 	track_edits,
 	help_text_fn = "eval_help",
 	category = "Playground"
 )]
-pub async fn eval(
+async fn eval_prefix(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	code: poise::CodeBlock,
+	code: CodeBlock,
 ) -> Result<(), Error> {
 	play_or_eval(ctx, flags, false, code, ResultHandling::Print).await
 }
 
+/// Evaluate a single Rust expression
+#[poise::command(slash_command, rename = "eval", category = "Playground")]
+#[allow(clippy::too_many_arguments)]
+async fn eval_slash(
+	ctx: Context<'_>,
+	#[description = "Expression to evaluate"] code: String,
+	#[description = "Release channel"] channel: Option<Channel>,
+	#[description = "Rust edition"] edition: Option<Edition>,
+	#[description = "Compilation mode"] mode: Option<Mode>,
+	#[description = "Show compiler warnings"] warn: Option<bool>,
+	#[description = "Only show the result to you"] ephemeral: Option<bool>,
+	#[description = "More flags, like `showtype=true`. See ?playflags"] flags: Option<String>,
+) -> Result<(), Error> {
+	defer_slash_command(ctx, ephemeral == Some(true)).await?;
+	let flags = slash_command_flags(channel, edition, mode, warn, ephemeral, flags.as_deref());
+	let code = CodeBlock {
+		code,
+		language: None,
+	};
+	play_or_eval(ctx, flags, false, code, ResultHandling::Print).await
+}
+
 #[must_use]
 pub fn eval_help() -> String {
 	generic_help(GenericHelp {
@@ -132,5 +1148,208 @@ pub fn eval_help() -> String {
 		warn: true,
 		run: false,
 		example_code: "code",
-	})
+	}) + MORE_FLAGS_HELP
+}
+
+/// List the extra flags understood by `?play`, `?playwarn` and `?eval`
+#[poise::command(prefix_command, slash_command, category = "Playground")]
+pub async fn playflags(ctx: Context<'_>) -> Result<(), Error> {
+	// Discord's message length limit
+	const MAX_MESSAGE_LENGTH: usize = 2000;
+
+	// Split between lines, so that no flag's description is torn apart
+	let mut message = String::new();
+	for line in play_flags_help().lines() {
+		if message.len() + line.len() + 1 > MAX_MESSAGE_LENGTH {
+			ctx.say(std::mem::take(&mut message)).await?;
+		}
+		message += line;
+		message += "\n";
+	}
+	ctx.say(message).await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn prepare(code: &str, flags: &mut CommandFlags) -> (String, String) {
+		let mut notes = String::new();
+		let code = prepare_code(
+			code.to_owned(),
+			flags,
+			ResultHandling::None,
+			false,
+			false,
+			&mut notes,
+		)
+		.unwrap();
+		(code, notes)
+	}
+
+	fn result(stdout: &str, stderr: &str) -> PlayResult {
+		PlayResult {
+			success: true,
+			stdout: stdout.to_owned(),
+			stderr: stderr.to_owned(),
+			exit_detail: String::new(),
+		}
+	}
+
+	#[test]
+	fn every_table_flag_with_help_is_listed() {
+		let help = play_flags_help();
+		let documented = BOOL_FLAGS
+			.iter()
+			.map(|flag| (flag.name, flag.help))
+			.chain(NUMBER_FLAGS.iter().map(|flag| (flag.name, flag.help)));
+		for (name, desc) in documented {
+			let listed = help
+				.lines()
+				.any(|line| line.starts_with(&format!("- {name}: ")));
+			assert_eq!(listed, desc.is_some(), "{name}");
+		}
+	}
+
+	#[test]
+	fn bare_statements_are_wrapped_in_main() {
+		let (code, notes) = prepare("println!(\"hi\");", &mut CommandFlags::default());
+		assert!(code.contains("fn main"));
+		assert_eq!(notes, "");
+	}
+
+	#[test]
+	fn code_transforms_leave_notes() {
+		let mut flags = CommandFlags {
+			seed: Some(1),
+			migrate: Some(Edition::E2021),
+			..Default::default()
+		};
+		let (code, notes) = prepare("fn main() { rand::thread_rng(); }", &mut flags);
+		assert!(code.contains("fn seeded_rng"));
+		assert!(notes.contains("`thread_rng()` can't be seeded"));
+		assert!(notes.contains("only fire with an older `edition`"));
+		assert!(flags.warn, "migration lints are warnings");
+	}
+
+	#[test]
+	fn cranelift_is_refused() {
+		for (channel, reason) in [
+			(Channel::Nightly, "only compiles with LLVM"),
+			(Channel::Stable, "needs `channel=nightly`"),
+		] {
+			let mut flags = CommandFlags {
+				channel,
+				backend: CodegenBackend::Cranelift,
+				..Default::default()
+			};
+			let error = prepare_code(
+				"fn main() {}".to_owned(),
+				&mut flags,
+				ResultHandling::None,
+				false,
+				false,
+				&mut String::new(),
+			)
+			.unwrap_err();
+			assert!(error.to_string().contains(reason), "{error}");
+		}
+
+		let mut flags = CommandFlags {
+			backend: CodegenBackend::Llvm,
+			..Default::default()
+		};
+		assert!(prepare("fn main() {}", &mut flags).1.is_empty());
+	}
+
+	#[test]
+	fn forbidden_crates_stop_the_run() {
+		let mut flags = CommandFlags::default();
+		flags.forbid.0.push("rand".to_owned());
+		let error = prepare_code(
+			"fn main() { rand::random::<u8>(); }".to_owned(),
+			&mut flags,
+			ResultHandling::None,
+			false,
+			false,
+			&mut String::new(),
+		)
+		.unwrap_err();
+		assert!(error.to_string().contains("`rand`"));
+	}
+
+	#[test]
+	fn output_is_deduplicated() {
+		let flags = CommandFlags {
+			dedupe: true,
+			..Default::default()
+		};
+		let mut result = result("a\na\na\nb\n", "");
+		assert_eq!(format_output(&mut result, &flags, ResultHandling::None), 0);
+		assert_eq!(result.stdout, dedupe_lines("a\na\na\nb\n"));
+	}
+
+	#[test]
+	fn merged_output_is_described_before_merging() {
+		let flags = CommandFlags {
+			merge: true,
+			..Default::default()
+		};
+		let mut result = result("out\n", "thread 'main' has overflowed its stack\n");
+		let footer = describe_output(&mut result, &flags, Duration::ZERO);
+		assert!(footer.contains("`stacksize=64`"));
+		assert!(footer.contains("relative order is lost"));
+		assert_eq!(result.stderr, "");
+		assert!(result.stdout.contains("has overflowed its stack"));
+		assert!(result.stdout.contains("out"));
+	}
+
+	#[test]
+	fn only_the_second_identical_run_is_cached() {
+		let mut cache = super::super::cache::ResultCache::default();
+		let request = PlaygroundRequest {
+			code: "fn main() {}",
+			channel: Channel::Nightly,
+			crate_type: CrateType::Binary,
+			edition: Edition::E2021,
+			mode: Mode::Debug,
+			tests: false,
+		};
+		let (ttl, now) = (Duration::from_mins(1), std::time::Instant::now());
+
+		let first = cache.get(&request.content_hash(), ttl, now);
+		assert!(!result_notes(0, first.is_some()).contains("(cached"));
+		cache.insert(request.content_hash(), result("hi\n", ""), now);
+
+		let second = cache.get(&request.content_hash(), ttl, now + Duration::from_secs(1));
+		assert_eq!(second.as_ref().unwrap().stdout, "hi\n");
+		assert!(result_notes(0, second.is_some()).contains("(cached"));
+	}
+
+	#[test]
+	fn total_time_is_shown_once_with_phasetime_and_clock() {
+		let flags = CommandFlags {
+			phasetime: true,
+			clock: true,
+			..Default::default()
+		};
+		let footer = describe_output(&mut result("", ""), &flags, Duration::from_millis(1500));
+		assert_eq!(footer.matches("took 1.50s total").count(), 1);
+		assert!(footer.contains("lines can't be timestamped"));
+
+		for flags in [
+			CommandFlags {
+				phasetime: true,
+				..Default::default()
+			},
+			CommandFlags {
+				clock: true,
+				..Default::default()
+			},
+		] {
+			let footer = describe_output(&mut result("", ""), &flags, Duration::from_millis(1500));
+			assert_eq!(footer.matches("took 1.50s total").count(), 1);
+		}
+	}
 }
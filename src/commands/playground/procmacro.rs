@@ -1,12 +1,13 @@
 use anyhow::Error;
 
+use crate::helpers::CodeBlock;
 use crate::types::Context;
 
 use super::{
-	api::{Channel, CrateType, Edition, Mode, PlayResult, PlaygroundRequest},
+	api::{execute, Channel, CrateType, Edition, Mode, PlayResult, PlaygroundRequest},
 	util::{
-		format_play_eval_stderr, generic_help, maybe_wrap, parse_flags, send_reply, stub_message,
-		GenericHelp, ResultHandling,
+		format_play_eval_stderr, generic_help, maybe_wrap, parse_invoker_flags, send_reply,
+		stub_message, GenericHelp, ResultHandling,
 	},
 };
 
@@ -20,15 +21,15 @@ use super::{
 pub async fn procmacro(
 	ctx: Context<'_>,
 	flags: poise::KeyValueArgs,
-	macro_code: poise::CodeBlock,
-	usage_code: poise::CodeBlock,
+	macro_code: CodeBlock,
+	usage_code: CodeBlock,
 ) -> Result<(), Error> {
 	ctx.say(stub_message(ctx)).await?;
 
 	let macro_code = macro_code.code;
 	let usage_code = maybe_wrap(&usage_code.code, ResultHandling::None);
 
-	let (flags, flag_parse_errors) = parse_flags(flags);
+	let (flags, flag_parse_errors) = parse_invoker_flags(ctx, flags);
 
 	let mut generated_code = format!(
 		stringify!(
@@ -73,11 +74,9 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }"#;
 
-	let mut result: PlayResult = ctx
-		.data()
-		.http
-		.post("https://play.rust-lang.org/execute")
-		.json(&PlaygroundRequest {
+	let mut result: PlayResult = execute(
+		ctx,
+		&PlaygroundRequest {
 			code: &generated_code,
 			channel: Channel::Nightly, // so that inner proc macro gets nightly too
 			// These flags only apply to the glue code
@@ -85,11 +84,9 @@ fn main() -> std::io::Result<()> {
 			edition: Edition::E2021,
 			mode: Mode::Debug,
 			tests: false,
-		})
-		.send()
-		.await?
-		.json()
-		.await?;
+		},
+	)
+	.await?;
 
 	// funky
 	result.stderr = format_play_eval_stderr(
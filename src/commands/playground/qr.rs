@@ -0,0 +1,56 @@
+//! QR codes for `qr=true`, so playground links can be opened on a phone
+
+use anyhow::Error;
+use qrcode::{Color, EcLevel, QrCode};
+
+/// Pixels per module, and modules of light border required around the code
+const SCALE: u32 = 8;
+const QUIET_ZONE: u32 = 4;
+
+/// Renders the text as a QR code to a PNG
+pub fn render_qr_png(text: &str) -> Result<Vec<u8>, Error> {
+	// Links are read from screens, so low error correction keeps the code small and easy to scan
+	let code = QrCode::with_error_correction_level(text, EcLevel::L)?;
+	let colors = code.to_colors();
+	let size = u32::try_from(code.width())?;
+	let pixels = (size + 2 * QUIET_ZONE) * SCALE;
+	let image = image::GrayImage::from_fn(pixels, pixels, |x, y| {
+		let (x, y) = (x / SCALE, y / SCALE);
+		let dark = (QUIET_ZONE..(QUIET_ZONE + size)).contains(&x)
+			&& (QUIET_ZONE..(QUIET_ZONE + size)).contains(&y)
+			&& colors[((y - QUIET_ZONE) * size + (x - QUIET_ZONE)) as usize] == Color::Dark;
+		image::Luma([if dark { 0 } else { 255 }])
+	});
+
+	let mut png = Vec::new();
+	image::DynamicImage::ImageLuma8(image).write_to(
+		&mut std::io::Cursor::new(&mut png),
+		image::ImageOutputFormat::Png,
+	)?;
+	Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn png_has_a_quiet_zone() {
+		let url = "https://play.rust-lang.org/?version=nightly&mode=debug&edition=2021&gist=\
+			0123456789abcdef0123456789abcdef";
+		let png = render_qr_png(url).unwrap();
+		let image = image::load_from_memory(&png).unwrap().to_luma8();
+
+		let size = u32::try_from(
+			QrCode::with_error_correction_level(url, EcLevel::L)
+				.unwrap()
+				.width(),
+		)
+		.unwrap();
+		assert_eq!(image.width(), (size + 2 * QUIET_ZONE) * SCALE);
+		let border = QUIET_ZONE * SCALE;
+		assert!((0..image.width()).all(|x| image.get_pixel(x, border - 1).0 == [255]));
+		// The top left finder pattern starts right after the quiet zone
+		assert_eq!(image.get_pixel(border, border).0, [0]);
+	}
+}
@@ -0,0 +1,142 @@
+//! Renders terminal output, including ANSI colors, to a PNG for `image=true`
+
+use std::sync::LazyLock;
+
+use anyhow::{bail, Error};
+
+/// Larger output is cut off, so the image stays readable and reasonably small
+const MAX_LINES: usize = 60;
+const MAX_WIDTH: u32 = 1600;
+
+const FONT_SIZE: f32 = 20.0;
+const LINE_HEIGHT: u32 = 24;
+const PADDING: u32 = 12;
+
+/// Discord's dark theme colors, so the image blends in
+const BACKGROUND: image::Rgba<u8> = image::Rgba([43, 45, 49, 255]);
+const FOREGROUND: image::Rgba<u8> = image::Rgba([201, 209, 217, 255]);
+
+/// The standard and bright colors of SGR codes 30-37 and 90-97
+const PALETTE: [[u8; 3]; 16] = [
+	[0, 0, 0],
+	[205, 49, 49],
+	[13, 188, 121],
+	[229, 229, 16],
+	[36, 114, 200],
+	[188, 63, 188],
+	[17, 168, 205],
+	[229, 229, 229],
+	[102, 102, 102],
+	[241, 76, 76],
+	[35, 209, 139],
+	[245, 245, 67],
+	[59, 142, 234],
+	[214, 112, 214],
+	[41, 184, 219],
+	[255, 255, 255],
+];
+
+static FONT: LazyLock<rusttype::Font<'_>> = LazyLock::new(|| {
+	rusttype::Font::try_from_bytes(include_bytes!("../../../assets/OpenSans.ttf"))
+		.expect("failed to load font")
+});
+
+/// Splits a line into runs of text and the color they're printed in. Escape sequences other than
+/// foreground colors are dropped
+fn colored_spans(line: &str, color: &mut image::Rgba<u8>) -> Vec<(image::Rgba<u8>, String)> {
+	let mut spans = Vec::new();
+	let mut rest = line;
+	while let Some(escape_pos) = rest.find('\x1b') {
+		if escape_pos > 0 {
+			spans.push((*color, rest[..escape_pos].to_owned()));
+		}
+		rest = &rest[(escape_pos + 1)..];
+
+		let Some(sequence) = rest.strip_prefix('[') else {
+			continue;
+		};
+		let Some(end) = sequence.find(|c: char| c.is_ascii_alphabetic()) else {
+			rest = "";
+			break;
+		};
+		if sequence[end..].starts_with('m') {
+			for code in sequence[..end].split(';') {
+				match code.parse::<usize>() {
+					Ok(0 | 39) | Err(_) => *color = FOREGROUND,
+					Ok(code @ 30..=37) => *color = to_rgba(PALETTE[code - 30]),
+					Ok(code @ 90..=97) => *color = to_rgba(PALETTE[code - 90 + 8]),
+					Ok(_) => {}
+				}
+			}
+		}
+		rest = &sequence[(end + 1)..];
+	}
+	if !rest.is_empty() {
+		spans.push((*color, rest.to_owned()));
+	}
+	spans
+}
+
+fn to_rgba([r, g, b]: [u8; 3]) -> image::Rgba<u8> {
+	image::Rgba([r, g, b, 255])
+}
+
+/// Horizontal distance the text takes up, including trailing whitespace
+fn advance_width(text: &str, scale: rusttype::Scale) -> f32 {
+	FONT.layout(text, scale, rusttype::point(0.0, 0.0))
+		.last()
+		.map_or(0.0, |glyph| {
+			glyph.position().x + glyph.unpositioned().h_metrics().advance_width
+		})
+}
+
+/// Renders the text to a PNG, with at most [`MAX_LINES`] lines and [`MAX_WIDTH`] pixels wide
+pub fn render_output_png(text: &str) -> Result<Vec<u8>, Error> {
+	let scale = rusttype::Scale::uniform(FONT_SIZE);
+
+	let mut color = FOREGROUND;
+	let lines = text
+		.lines()
+		.take(MAX_LINES)
+		.map(|line| colored_spans(&line.replace('\t', "    "), &mut color))
+		.collect::<Vec<_>>();
+	if lines.is_empty() {
+		bail!("no output to render");
+	}
+
+	let text_width = lines
+		.iter()
+		.map(|spans| {
+			spans
+				.iter()
+				.map(|(_, text)| advance_width(text, scale))
+				.sum::<f32>()
+		})
+		.fold(0.0, f32::max);
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // capped right away
+	let width = (text_width.ceil() as u32)
+		.saturating_add(2 * PADDING)
+		.min(MAX_WIDTH);
+	#[allow(clippy::cast_possible_truncation)] // at most MAX_LINES lines
+	let height = lines.len() as u32 * LINE_HEIGHT + 2 * PADDING;
+
+	let mut image = image::RgbaImage::from_pixel(width, height, BACKGROUND);
+	for (i, spans) in lines.iter().enumerate() {
+		#[allow(clippy::cast_possible_truncation)] // at most MAX_LINES lines
+		let y = (PADDING + i as u32 * LINE_HEIGHT) as i32;
+		let mut x = 0.0;
+		for (color, text) in spans {
+			#[allow(clippy::cast_possible_truncation)] // pixel coordinates are small
+			let left = PADDING as i32 + x as i32;
+			imageproc::drawing::draw_text_mut(&mut image, *color, left, y, scale, &FONT, text);
+			x += advance_width(text, scale);
+		}
+	}
+
+	let mut png = Vec::new();
+	image::DynamicImage::ImageRgba8(image).write_to(
+		&mut std::io::Cursor::new(&mut png),
+		image::ImageOutputFormat::Png,
+	)?;
+	Ok(png)
+}
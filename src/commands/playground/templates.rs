@@ -0,0 +1,115 @@
+//! Predefined templates which wrap the user's code, selected via the `template=name` flag
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use anyhow::{bail, Error};
+
+use super::util::{maybe_wrapped_with_main, ResultHandling};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+	/// Prepends `#![allow(unused)]` to silence the usual snippet noise
+	AllowUnused,
+	/// Wraps the code in `#[tokio::main] async fn main()`
+	TokioMain,
+}
+
+impl Template {
+	/// All templates, in the order they're listed in help messages
+	pub const ALL: &'static [Template] = &[Template::AllowUnused, Template::TokioMain];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Template::AllowUnused => "allow-unused",
+			Template::TokioMain => "tokio-main",
+		}
+	}
+
+	/// Applies the template to the user's code. Code without a `fn main` is wrapped in one
+	/// according to `result_handling`, just like [`super::util::maybe_wrapped`] does.
+	///
+	/// Fails if the template provides its own main function but the code already defines one.
+	pub fn apply(
+		self,
+		code: &str,
+		result_handling: ResultHandling,
+		unsf: bool,
+		pretty: bool,
+	) -> Result<String, Error> {
+		match self {
+			Template::AllowUnused => Ok(maybe_wrapped_with_main(
+				&format!("#![allow(unused)]\n{code}"),
+				"fn main()",
+				result_handling,
+				unsf,
+				pretty,
+			)
+			.into_owned()),
+			Template::TokioMain => match maybe_wrapped_with_main(
+				code,
+				"#[tokio::main]\nasync fn main()",
+				result_handling,
+				unsf,
+				pretty,
+			) {
+				Cow::Owned(wrapped) => Ok(wrapped),
+				Cow::Borrowed(_) => bail!(
+					"template `{}` can't be used with code that already defines `fn main` or \
+					doesn't parse",
+					self.name()
+				),
+			},
+		}
+	}
+}
+
+impl FromStr for Template {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Error> {
+		match Template::ALL.iter().find(|template| template.name() == s) {
+			Some(&template) => Ok(template),
+			None => bail!("invalid template `{}`", s),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn templates_are_found_by_name() {
+		for &template in Template::ALL {
+			assert_eq!(template.name().parse::<Template>().unwrap(), template);
+		}
+		assert!("tokio_main".parse::<Template>().is_err());
+	}
+
+	#[test]
+	fn allow_unused_is_hoisted_above_the_generated_main() {
+		let code = Template::AllowUnused
+			.apply("let x = 1;", ResultHandling::None, false, false)
+			.unwrap();
+		assert!(code.starts_with("#![allow(unused)]\nfn main() {\n"));
+		assert!(code.contains("let x = 1;"));
+		syn::parse_file(&code).unwrap();
+	}
+
+	#[test]
+	fn tokio_main_wraps_the_code_in_an_async_main() {
+		let code = Template::TokioMain
+			.apply("sleep(d).await;", ResultHandling::Discard, false, false)
+			.unwrap();
+		assert!(code.starts_with("#[tokio::main]\nasync fn main() {"));
+		syn::parse_file(&code).unwrap();
+
+		let error = Template::TokioMain
+			.apply("fn main() {}", ResultHandling::None, false, false)
+			.unwrap_err();
+		assert!(error
+			.to_string()
+			.starts_with("template `tokio-main` can't be used"));
+	}
+}
@@ -1,12 +1,17 @@
 use std::borrow::Cow;
+use std::fmt::Write as _;
 
 use poise::serenity_prelude as serenity;
 use serenity::ComponentInteraction;
 
+use crate::config::Config;
 use crate::types::Context;
 use crate::Error;
 
 use super::api;
+use super::qr;
+use super::render;
+use super::templates::Template;
 
 // Small thing about multiline strings: while hacking on this file I was unsure how to handle
 // trailing newlines in multiline strings:
@@ -21,19 +26,24 @@ use super::api;
 
 /// Returns the parsed flags and a String of parse errors. The parse error string will have a
 /// trailing newline (except if empty)
-pub fn parse_flags(mut args: poise::KeyValueArgs) -> (api::CommandFlags, String) {
+pub fn parse_flags(config: &Config, mut args: poise::KeyValueArgs) -> (api::CommandFlags, String) {
 	let mut errors = String::new();
 
 	let mut flags = api::CommandFlags {
-		channel: api::Channel::Nightly,
-		mode: api::Mode::Debug,
-		edition: api::Edition::E2021,
-		warn: false,
-		run: false,
+		channel: config.default_channel,
+		..Default::default()
 	};
 
 	macro_rules! pop_flag {
-		($flag_name:literal, $flag_field:expr) => {
+		($flag_name:expr, Some($flag_field:expr)) => {
+			if let Some(flag) = args.0.remove($flag_name) {
+				match flag.parse() {
+					Ok(x) => $flag_field = Some(x),
+					Err(e) => errors += &format!("{}\n", e),
+				}
+			}
+		};
+		($flag_name:expr, $flag_field:expr) => {
 			if let Some(flag) = args.0.remove($flag_name) {
 				match flag.parse() {
 					Ok(x) => $flag_field = x,
@@ -46,8 +56,78 @@ pub fn parse_flags(mut args: poise::KeyValueArgs) -> (api::CommandFlags, String)
 	pop_flag!("channel", flags.channel);
 	pop_flag!("mode", flags.mode);
 	pop_flag!("edition", flags.edition);
-	pop_flag!("warn", flags.warn);
-	pop_flag!("run", flags.run);
+	pop_flag!("template", Some(flags.template));
+	pop_flag!("migrate", Some(flags.migrate));
+	pop_flag!("forbid", flags.forbid);
+	pop_flag!("editions", Some(flags.editions));
+	pop_flag!("deadline", Some(flags.deadline));
+	pop_flag!("seed", Some(flags.seed));
+	for flag in api::BOOL_FLAGS {
+		pop_flag!(flag.name, *(flag.get_mut)(&mut flags));
+	}
+	for flag in api::NUMBER_FLAGS {
+		pop_flag!(flag.name, Some(*(flag.get_mut)(&mut flags)));
+	}
+
+	// `async=true` is shorthand for the most commonly needed template
+	let mut async_main = false;
+	pop_flag!("async", async_main);
+	if async_main {
+		match flags.template {
+			None | Some(Template::TokioMain) => flags.template = Some(Template::TokioMain),
+			Some(other) => {
+				let _ = writeln!(
+					errors,
+					"`async=true` can't be combined with `template={}`",
+					other.name()
+				);
+			}
+		}
+	}
+
+	// The playground doesn't take rustc flags, so the allowlisted ones are mapped onto its
+	// settings where possible
+	let mut rustflags = api::RustFlags::default();
+	pop_flag!("rustflags", rustflags);
+	for unsupported in rustflags.apply_to(&mut flags) {
+		let _ = writeln!(
+			errors,
+			"`{unsupported}` can't be applied by the playground, try `?godbolt`"
+		);
+	}
+
+	// Neither is there a way to pass `--cap-lints`, but Godbolt takes arbitrary rustc flags
+	let mut cap_lints = None::<api::CapLints>;
+	pop_flag!("caplints", Some(cap_lints));
+	if let Some(cap_lints) = cap_lints {
+		let _ = writeln!(
+			errors,
+			"the playground can't cap lints, try `?godbolt --cap-lints={}`",
+			cap_lints.name()
+		);
+	}
+
+	// The playground always compiles with LLVM, so other backends are refused before running
+	pop_flag!("backend", flags.backend);
+
+	// Miri always runs with its default seed on the playground, so every run would be the same
+	let mut miri_seeds = None::<std::num::NonZeroUsize>;
+	pop_flag!("miriseeds", Some(miri_seeds));
+	if let Some(miri_seeds) = miri_seeds {
+		let _ = writeln!(
+			errors,
+			"the playground can't pass `-Zmiri-seed` to Miri, try \
+			`MIRIFLAGS=\"-Zmiri-many-seeds=0..{miri_seeds}\" cargo miri run` locally"
+		);
+	}
+
+	// Debug info only shows up in compiled output, which the playground doesn't give us control of
+	if let Some(debuginfo) = args.0.remove("debuginfo") {
+		let _ = writeln!(
+			errors,
+			"`debuginfo` only affects asm output, try `?godbolt debuginfo={debuginfo}`"
+		);
+	}
 
 	for (remaining_flag, _) in args.0 {
 		errors += &format!("unknown flag `{remaining_flag}`\n");
@@ -56,6 +136,134 @@ pub fn parse_flags(mut args: poise::KeyValueArgs) -> (api::CommandFlags, String)
 	(flags, errors)
 }
 
+/// [`parse_flags`] for the invoker of the command, turning off the flags they may not use
+pub fn parse_invoker_flags(
+	ctx: Context<'_>,
+	args: poise::KeyValueArgs,
+) -> (api::CommandFlags, String) {
+	let (mut flags, mut errors) = parse_flags(&ctx.data().config, args);
+	errors += &restrict_moderator_flags(&mut flags, crate::checks::is_moderator(ctx));
+	(flags, errors)
+}
+
+/// Turns off `pin` and `webhook` unless the invoker is a moderator, as they reach beyond the
+/// invoker's own reply. Returns a note for each flag that was turned off, with a trailing newline
+/// (except if empty)
+pub fn restrict_moderator_flags(flags: &mut api::CommandFlags, is_moderator: bool) -> String {
+	let mut notes = String::new();
+	if is_moderator {
+		return notes;
+	}
+	for (name, enabled) in [("pin", &mut flags.pin), ("webhook", &mut flags.webhook)] {
+		if std::mem::take(enabled) {
+			let _ = writeln!(notes, "`{name}` is only available to moderators");
+		}
+	}
+	notes
+}
+
+/// Splits a `//! play: key=value ...` directive off the first line of the code, so snippets can
+/// carry their own flags. The directive's flags are added to `flags`, without overriding the ones
+/// given to the command. Returns the remaining code and a String of parse errors (with a trailing
+/// newline, except if empty)
+pub fn split_flag_directive<'a>(
+	code: &'a str,
+	flags: &mut poise::KeyValueArgs,
+) -> (&'a str, String) {
+	let mut errors = String::new();
+
+	let (first_line, rest) = code.split_once('\n').unwrap_or((code, ""));
+	let Some(directive) = first_line.trim().strip_prefix("//! play:") else {
+		return (code, errors);
+	};
+
+	for token in directive.split_whitespace() {
+		match token.split_once('=') {
+			Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+				flags
+					.0
+					.entry(key.to_owned())
+					.or_insert_with(|| value.to_owned());
+			}
+			_ => {
+				let _ = writeln!(
+					errors,
+					"ignoring `{token}` in the `//! play:` line, expected `key=value`"
+				);
+			}
+		}
+	}
+	(rest, errors)
+}
+
+/// Turns the typed options of a slash command into the flags its prefix command counterpart
+/// would have received, so both go through [`parse_flags`]. `extra_flags` takes the remaining
+/// flags in the prefix command syntax, like `tail=10 dedupe=true`
+#[must_use]
+pub fn slash_command_flags(
+	channel: Option<api::Channel>,
+	edition: Option<api::Edition>,
+	mode: Option<api::Mode>,
+	warn: Option<bool>,
+	ephemeral: Option<bool>,
+	extra_flags: Option<&str>,
+) -> poise::KeyValueArgs {
+	let mut flags = poise::KeyValueArgs::default();
+	for token in extra_flags.unwrap_or("").split_whitespace() {
+		// Malformed flags end up as unknown flags, so `parse_flags` reports them
+		let (key, value) = token.split_once('=').unwrap_or((token, ""));
+		flags.0.insert(key.to_owned(), value.to_owned());
+	}
+
+	let typed_flags = [
+		("channel", channel.map(api::Channel::name)),
+		("edition", edition.map(api::Edition::name)),
+		("mode", mode.map(api::Mode::name)),
+		("warn", warn.map(bool_name)),
+		("ephemeral", ephemeral.map(bool_name)),
+	];
+	for (key, value) in typed_flags {
+		if let Some(value) = value {
+			flags.0.insert(key.to_owned(), value.to_owned());
+		}
+	}
+	flags
+}
+
+fn bool_name(value: bool) -> &'static str {
+	if value {
+		"true"
+	} else {
+		"false"
+	}
+}
+
+/// Acknowledges a slash command invocation right away, because running code takes longer than
+/// the three seconds Discord waits for a response
+pub async fn defer_slash_command(ctx: Context<'_>, ephemeral: bool) -> Result<(), Error> {
+	if ephemeral {
+		ctx.defer_ephemeral().await?;
+	} else {
+		ctx.defer().await?;
+	}
+	Ok(())
+}
+
+/// Combines a prefix-only command with a slash-only command of the same name. poise can't
+/// generate both from one function for the playground commands, because the prefix versions take
+/// free-form flags and a code block, which slash commands have no equivalent for
+#[must_use]
+pub fn with_slash_command(
+	prefix_command: poise::Command<crate::types::Data, Error>,
+	slash_command: poise::Command<crate::types::Data, Error>,
+) -> poise::Command<crate::types::Data, Error> {
+	poise::Command {
+		slash_action: slash_command.slash_action,
+		parameters: slash_command.parameters,
+		..prefix_command
+	}
+}
+
 #[derive(Clone, Copy)]
 pub struct GenericHelp<'a> {
 	pub command: &'a str,
@@ -160,6 +368,8 @@ pub enum ResultHandling {
 	Discard,
 	/// Print the result with `println!("{:?}")`
 	Print,
+	/// Print the result with `println!("{:?}")`, followed by its type
+	PrintWithType,
 }
 
 pub fn hoise_crate_attributes(code: &str, after_crate_attrs: &str, after_code: &str) -> String {
@@ -170,13 +380,29 @@ pub fn hoise_crate_attributes(code: &str, after_crate_attrs: &str, after_code: &
 	// First go through the input lines and extract the crate attributes at the start. Those will
 	// be put right at the beginning of the generated code, else they won't work (crate attributes
 	// need to be at the top of the file)
+	// Number of brackets of the current crate attribute still open, for attributes spanning
+	// multiple lines like `#![feature(\n\ttry_blocks,\n\tnever_type\n)]`
+	let mut open_brackets = 0_isize;
+	// Comments and empty lines since the last crate attribute. Comments may come before crate
+	// attributes, but they may just as well belong to the first item, like `///` doc comments do,
+	// so they're only moved along if another crate attribute follows
+	let mut pending = Vec::new();
 	while let Some(line) = lines.peek() {
-		let line = line.trim();
-		if line.starts_with("#![") {
-			output.push_str(line);
+		let trimmed = line.trim();
+		if open_brackets > 0 || trimmed.starts_with("#![") || trimmed.starts_with("//!") {
+			for pending_line in pending.drain(..) {
+				output.push_str(str::trim(pending_line));
+				output.push('\n');
+			}
+			// `//!` doc comments are crate attributes too, but only span a single line
+			if !trimmed.starts_with("//!") {
+				open_brackets += trimmed.matches('[').count() as isize;
+				open_brackets -= trimmed.matches(']').count() as isize;
+			}
+			output.push_str(trimmed);
 			output.push('\n');
-		} else if line.is_empty() {
-			// do nothing, maybe more crate attributes are coming
+		} else if trimmed.starts_with("//") || trimmed.is_empty() {
+			pending.push(*line);
 		} else {
 			break;
 		}
@@ -186,7 +412,7 @@ pub fn hoise_crate_attributes(code: &str, after_crate_attrs: &str, after_code: &
 	output.push_str(after_crate_attrs);
 
 	// Write the rest of the lines that don't contain crate attributes
-	for line in lines {
+	for line in pending.into_iter().chain(lines) {
 		output.push_str(line);
 		output.push('\n');
 	}
@@ -209,6 +435,18 @@ pub fn maybe_wrapped(
 	unsf: bool,
 	pretty: bool,
 ) -> Cow<'_, str> {
+	maybe_wrapped_with_main(code, "fn main()", result_handling, unsf, pretty)
+}
+
+/// Like [`maybe_wrapped`], but lets the caller choose the signature of the generated main
+/// function, e.g. `#[tokio::main]\nasync fn main()`
+pub fn maybe_wrapped_with_main<'a>(
+	code: &'a str,
+	main_signature: &str,
+	result_handling: ResultHandling,
+	unsf: bool,
+	pretty: bool,
+) -> Cow<'a, str> {
 	#[allow(clippy::wildcard_imports)]
 	use syn::{parse::Parse, *};
 
@@ -230,7 +468,9 @@ pub fn maybe_wrapped(
 		}
 	}
 
-	let Ok(Inline { .. }) = parse_str::<Inline>(code) else {
+	let inline = parse_str::<Inline>(code);
+	forget_parsed_sources();
+	let Ok(Inline { .. }) = inline else {
 		return Cow::Borrowed(code);
 	};
 
@@ -239,12 +479,14 @@ pub fn maybe_wrapped(
 
 	// fn main boilerplate
 	let mut after_crate_attrs = match result_handling {
-		ResultHandling::None => "fn main() {\n",
-		ResultHandling::Discard => "fn main() { let _ = {\n",
-		ResultHandling::Print if pretty => "fn main() { println!(\"{:#?}\", {\n",
-		ResultHandling::Print => "fn main() { println!(\"{:?}\", {\n",
-	}
-	.to_owned();
+		ResultHandling::None => format!("{main_signature} {{\n"),
+		ResultHandling::Discard => format!("{main_signature} {{ let _ = {{\n"),
+		ResultHandling::Print if pretty => {
+			format!("{main_signature} {{ println!(\"{{:#?}}\", {{\n")
+		}
+		ResultHandling::Print => format!("{main_signature} {{ println!(\"{{:?}}\", {{\n"),
+		ResultHandling::PrintWithType => format!("{main_signature} {{ let value = {{\n"),
+	};
 
 	if unsf {
 		after_crate_attrs = format!("{after_crate_attrs}unsafe {{");
@@ -252,11 +494,15 @@ pub fn maybe_wrapped(
 
 	// fn main boilerplate counterpart
 	let mut after_code = match result_handling {
-		ResultHandling::None => "}",
-		ResultHandling::Discard => "}; }",
-		ResultHandling::Print => "}); }",
-	}
-	.to_owned();
+		ResultHandling::None => "}".to_owned(),
+		ResultHandling::Discard => "}; }".to_owned(),
+		ResultHandling::Print => "}); }".to_owned(),
+		ResultHandling::PrintWithType => format!(
+			"}}; println!(\"{}\", value); \
+			println!(\"type: {{}}\", std::any::type_name_of_val(&value)); }}",
+			if pretty { "{:#?}" } else { "{:?}" }
+		),
+	};
 
 	if unsf {
 		after_code = format!("}}{after_code}");
@@ -269,6 +515,284 @@ pub fn maybe_wrapped(
 	))
 }
 
+/// Removes `///`, `//!`, `/** */` and `/*! */` doc comments from the code, for `stripdocs=true`.
+/// Lines that only held a doc comment are removed entirely. Comment-like text in strings, char literals and other
+/// comments is left alone
+pub fn strip_doc_comments(code: &str) -> String {
+	let chars = code.chars().collect::<Vec<_>>();
+	let mut output = String::new();
+	// Start of the current line in `output`, to remove lines that only held a doc comment
+	let mut line_start = 0;
+	let mut i = 0;
+
+	while i < chars.len() {
+		let rest = &chars[i..];
+		let line_end = rest
+			.iter()
+			.position(|&c| c == '\n')
+			.map_or(chars.len(), |n| i + n);
+
+		// Index after the token starting at `i`, which is copied as-is
+		let end = match rest {
+			['/', '/', '/', '/', ..] => line_end,
+			['/', '/', '/' | '!', ..] => {
+				if output[line_start..].trim().is_empty() {
+					output.truncate(line_start);
+					// Also skip the newline, so no empty line is left behind
+					i = line_end + 1;
+				} else {
+					output.truncate(output.trim_end().len());
+					i = line_end;
+				}
+				continue;
+			}
+			['/', '/', ..] => line_end,
+			['/', '*', ..] => {
+				// Block comments nest
+				let mut depth = 0;
+				let mut end = i;
+				while end < chars.len() {
+					match &chars[end..] {
+						['/', '*', ..] => {
+							depth += 1;
+							end += 2;
+						}
+						['*', '/', ..] => {
+							depth -= 1;
+							end += 2;
+							if depth == 0 {
+								break;
+							}
+						}
+						_ => end += 1,
+					}
+				}
+
+				// `/** */` and `/*! */` are doc comments, but `/**/` and `/*** */` aren't
+				let is_doc_comment = matches!(rest, ['/', '*', '!', ..])
+					|| matches!(rest, ['/', '*', '*', c, ..] if *c != '*' && *c != '/');
+				if is_doc_comment {
+					let end = end.min(chars.len());
+					let rest_of_line = chars[end..].iter().take_while(|&&c| c != '\n');
+					if output[line_start..].trim().is_empty()
+						&& rest_of_line.clone().all(|c| c.is_whitespace())
+					{
+						// Also skip the newline, so no empty line is left behind
+						output.truncate(line_start);
+						i = end + rest_of_line.count() + 1;
+					} else {
+						i = end;
+					}
+					continue;
+				}
+				end
+			}
+			['"', ..] => {
+				let mut end = i + 1;
+				while end < chars.len() && chars[end] != '"' {
+					end += if chars[end] == '\\' { 2 } else { 1 };
+				}
+				end + 1
+			}
+			['r', '#' | '"', ..] | ['b', 'r', '#' | '"', ..]
+				if i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_') =>
+			{
+				let prefix_len = if rest[0] == 'b' { 2 } else { 1 };
+				let hashes = rest[prefix_len..].iter().take_while(|&&c| c == '#').count();
+				if rest.get(prefix_len + hashes) == Some(&'"') {
+					let mut end = i + prefix_len + hashes + 1;
+					while end < chars.len()
+						&& !(chars[end] == '"'
+							&& chars[(end + 1)..].iter().take_while(|&&c| c == '#').count()
+								>= hashes)
+					{
+						end += 1;
+					}
+					end + 1 + hashes
+				} else {
+					// A raw identifier like `r#type`
+					i + prefix_len + hashes
+				}
+			}
+			// Char literals, which may contain quotes, even escaped ones like `'\''`. Anything else
+			// is a lifetime
+			['\'', '\\', _, ..] => rest[3..]
+				.iter()
+				.position(|&c| c == '\'')
+				.map_or(chars.len(), |n| i + 3 + n + 1),
+			['\'', _, '\'', ..] => i + 3,
+			['\n', ..] => {
+				output.push('\n');
+				line_start = output.len();
+				i += 1;
+				continue;
+			}
+			_ => i + 1,
+		};
+
+		let end = end.min(chars.len());
+		output.extend(&chars[i..end]);
+		if chars[i..end].contains(&'\n') {
+			line_start = output.rfind('\n').map_or(0, |pos| pos + 1);
+		}
+		i = end;
+	}
+	output
+}
+
+/// Message to show in place of output that doesn't fit into a Discord message. Unless disabled,
+/// this creates a gist and links to it
+async fn output_too_large_message(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &api::CommandFlags,
+) -> String {
+	let config = &ctx.data().config;
+	if config.no_gist {
+		return "Output too large.".to_owned();
+	}
+
+	format!(
+		"Output too large. Playground link: <{}>",
+		api::url_from_gist(
+			config,
+			flags,
+			&post_shared_gist(ctx, code, flags).await.unwrap_or_default()
+		),
+	)
+}
+
+/// Shares the code as a gist and returns its ID. All gists of a run go through this, so they show
+/// the same code: without doc comments for `stripdocs=true`, and formatted for `fmtgist=true`
+async fn post_shared_gist(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &api::CommandFlags,
+) -> Result<String, Error> {
+	let stripped = flags.stripdocs.then(|| strip_doc_comments(code));
+	let code = stripped.as_deref().unwrap_or(code);
+
+	// With `fmtgist=true`, share the code nicely formatted, or as-is if rustfmt fails on it
+	let mut formatted = None;
+	if flags.fmtgist {
+		match api::apply_online_rustfmt(ctx, code, flags.edition).await {
+			Ok(result) if result.success => formatted = Some(result.stdout),
+			Ok(_) => {}
+			Err(e) => tracing::warn!("Couldn't format code for gist: {}", e),
+		}
+	}
+	api::post_gist(ctx, formatted.as_deref().unwrap_or(code)).await
+}
+
+/// Markdown for a GitHub issue reproducing the run, for `issue=true`
+pub fn issue_body(
+	code: &str,
+	flags: &api::CommandFlags,
+	version: Option<&api::VersionResponse>,
+	output: &str,
+	playground_url: Option<&str>,
+) -> String {
+	let mut body = String::new();
+	let _ = writeln!(body, "### Code\n\n```rust\n{}\n```\n", code.trim_end());
+
+	body += "### Meta\n\n";
+	match version {
+		Some(version) => {
+			let short_hash = version.hash.get(..9).unwrap_or(&version.hash);
+			let _ = writeln!(
+				body,
+				"`rustc {} ({short_hash} {})`",
+				version.version, version.date
+			);
+		}
+		None => body += "rustc version unknown\n",
+	}
+	let _ = writeln!(
+		body,
+		"Ran on the Rust playground with {}\n",
+		flags.toolchain_summary()
+	);
+
+	let _ = writeln!(body, "### Output\n\n```\n{}\n```", output.trim_end());
+
+	if let Some(url) = playground_url {
+		let _ = writeln!(body, "\n[Playground link]({url})");
+	}
+	body
+}
+
+/// Replies with the result as a GitHub issue body, attached as a file because it contains code
+/// blocks itself
+pub async fn send_issue_reply(
+	ctx: Context<'_>,
+	result: &api::PlayResult,
+	code: &str,
+	flags: &api::CommandFlags,
+	flag_parse_errors: &str,
+) -> Result<(), Error> {
+	let version = api::fetch_version(ctx, flags.channel)
+		.await
+		.inspect_err(|e| tracing::warn!("Couldn't fetch playground version: {}", e))
+		.ok();
+	let config = &ctx.data().config;
+	let playground_url = if config.no_gist {
+		None
+	} else {
+		post_shared_gist(ctx, code, flags)
+			.await
+			.inspect_err(|e| tracing::warn!("Couldn't create gist: {}", e))
+			.ok()
+			.map(|gist_id| api::url_from_gist(config, flags, &gist_id))
+	};
+	let output = crate::helpers::merge_output_and_errors(&result.stdout, &result.stderr);
+	let body = issue_body(
+		code,
+		flags,
+		version.as_ref(),
+		&output,
+		playground_url.as_deref(),
+	);
+
+	let flag_parse_errors = neutralize_mentions_if_safe(flags, flag_parse_errors);
+	send_result(
+		ctx,
+		flags,
+		poise::CreateReply::default()
+			.content(format!("{flag_parse_errors}Issue body, ready to paste:"))
+			.attachment(serenity::CreateAttachment::bytes(body, "issue.md"))
+			.ephemeral(flags.ephemeral),
+	)
+	.await?;
+	Ok(())
+}
+
+/// Replies with the diff in a code block highlighted as such. If it's too long, it's cut off and
+/// a gist of the code is linked instead, like with other output
+pub async fn send_diff_reply(
+	ctx: Context<'_>,
+	diff: &str,
+	code: &str,
+	flags: &api::CommandFlags,
+	flag_parse_errors: &str,
+) -> Result<(), Error> {
+	let text = crate::helpers::trim_text(
+		&neutralize_mentions_if_safe(flags, &format!("{flag_parse_errors}```diff\n{diff}")),
+		"```",
+		output_too_large_message(ctx, code, flags),
+	)
+	.await;
+
+	send_result(
+		ctx,
+		flags,
+		poise::CreateReply::default()
+			.content(text)
+			.ephemeral(flags.ephemeral),
+	)
+	.await?;
+	Ok(())
+}
+
 /// Send a Discord reply with the formatted contents of a Playground result
 pub async fn send_reply(
 	ctx: Context<'_>,
@@ -277,54 +801,151 @@ pub async fn send_reply(
 	flags: &api::CommandFlags,
 	flag_parse_errors: &str,
 ) -> Result<(), Error> {
-	let result = crate::helpers::merge_output_and_errors(&result.stdout, &result.stderr);
+	send_reply_with_footer(ctx, result, code, flags, flag_parse_errors, "").await
+}
+
+/// Like [`send_reply`], but appends `footer` on a new line after the code block
+pub async fn send_reply_with_footer(
+	ctx: Context<'_>,
+	result: api::PlayResult,
+	code: &str,
+	flags: &api::CommandFlags,
+	flag_parse_errors: &str,
+	footer: &str,
+) -> Result<(), Error> {
+	let mut flag_parse_errors = Cow::Borrowed(flag_parse_errors);
+
+	let mut footer = Cow::Borrowed(footer);
+	let mut qr_code = None;
+	if flags.qr {
+		let shared = playground_url(ctx, code, flags).await;
+		match shared.and_then(|url| qr_code_attachment(&footer, &url)) {
+			Ok((with_link, attachment)) => {
+				footer = with_link.into();
+				qr_code = Some(attachment);
+			}
+			Err(e) => {
+				tracing::warn!("Couldn't create QR code: {}", e);
+				flag_parse_errors += "couldn't create a QR code of the playground link\n";
+			}
+		}
+	}
+	let with_qr_code = |reply: poise::CreateReply| match &qr_code {
+		Some(qr_code) => reply.attachment(qr_code.clone()),
+		None => reply,
+	};
+
+	// Nobody is pinged by the reply itself, but bots relaying it may not be as careful. Flag
+	// errors and footers can echo the invocation, so they're neutralized along with the output
+	if flags.safe {
+		flag_parse_errors = neutralize_mentions(&flag_parse_errors).into();
+		footer = neutralize_mentions(&footer).into();
+	}
+
+	let kill_reason = kill_reason(&result);
+	// The text reply shows this after the code block, the others before the footer
+	let kill_note = kill_reason.map_or(String::new(), |reason| format!("{}\n", reason.note()));
+
+	if flags.image {
+		// Discord hides attachments with this prefix behind a spoiler
+		let file_name = if flags.collapse {
+			"SPOILER_output.png"
+		} else {
+			"output.png"
+		};
+		let output = merged_output(&result, flags);
+		match render::render_output_png(&output) {
+			Ok(png) => {
+				send_result(
+					ctx,
+					flags,
+					with_qr_code(poise::CreateReply::default())
+						.content(format!("{flag_parse_errors}{kill_note}{footer}"))
+						.attachment(serenity::CreateAttachment::bytes(png, file_name))
+						.ephemeral(flags.ephemeral),
+				)
+				.await?;
+				return Ok(());
+			}
+			Err(e) => {
+				tracing::warn!("Couldn't render output to an image: {}", e);
+				flag_parse_errors += "couldn't render the output to an image, here it is as text\n";
+			}
+		}
+	}
+	let flag_parse_errors = &*flag_parse_errors;
+
+	let result = if flags.safe {
+		api::PlayResult {
+			success: result.success,
+			stdout: neutralize_mentions(&result.stdout),
+			stderr: neutralize_mentions(&result.stderr),
+			exit_detail: result.exit_detail,
+		}
+	} else {
+		result
+	};
+
+	if flags.embed {
+		let footer = kill_note + &footer;
+		return send_embed_reply(ctx, &result, code, flags, flag_parse_errors, &footer).await;
+	}
+
+	let result = merged_output(&result, flags);
+	let footer = match footer.trim_end() {
+		"" => String::new(),
+		footer => format!("\n{footer}"),
+	};
 
 	// Discord displays empty code blocks weirdly if they're not formatted in a specific style,
 	// so we special-case empty code blocks
 	if result.trim().is_empty() {
-		ctx.say(format!("{flag_parse_errors}``` ```")).await?;
+		send_result(
+			ctx,
+			flags,
+			with_qr_code(poise::CreateReply::default())
+				.content(format!("{flag_parse_errors}``` ```{footer}"))
+				.ephemeral(flags.ephemeral),
+		)
+		.await?;
 		return Ok(());
 	}
 
-	let timeout =
-		result.contains("Killed") && result.contains("timeout") && result.contains("--signal=KILL");
+	let timeout = kill_reason == Some(KillReason::Timeout);
 
-	let mut text_end = String::from("```");
-	if timeout {
-		text_end += "Playground timeout detected";
+	// Discord has no collapsible sections, but spoilers hide the output until clicked
+	let spoiler = if flags.collapse { "||" } else { "" };
+	let mut text_end = format!("```{spoiler}");
+	if let Some(kill_reason) = kill_reason {
+		text_end += kill_reason.note();
 	}
+	text_end += &footer;
 
 	let text = crate::helpers::trim_text(
-		&format!("{flag_parse_errors}```rust\n{result}"),
+		&format!("{flag_parse_errors}{spoiler}```rust\n{result}"),
 		&text_end,
-		async {
-			format!(
-				"Output too large. Playground link: <{}>",
-				api::url_from_gist(flags, &api::post_gist(ctx, code).await.unwrap_or_default()),
-			)
-		},
+		output_too_large_message(ctx, code, flags),
 	)
 	.await;
 
 	let custom_id = ctx.id().to_string();
 
-	let response = ctx
-		.send({
-			let mut b = poise::CreateReply::default().content(text);
-			if timeout {
-				b = b.components(vec![serenity::CreateActionRow::Buttons(vec![
-					serenity::CreateButton::new(&custom_id)
-						.label("Retry")
-						.style(serenity::ButtonStyle::Primary),
-				])]);
-			}
-			b
-		})
-		.await?;
+	let response = send_result(ctx, flags, {
+		let mut b = with_qr_code(poise::CreateReply::default())
+			.content(text)
+			.ephemeral(flags.ephemeral);
+		if timeout {
+			b = b.components(vec![serenity::CreateActionRow::Buttons(vec![
+				serenity::CreateButton::new(&custom_id)
+					.label("Retry")
+					.style(serenity::ButtonStyle::Primary),
+			])]);
+		}
+		b
+	})
+	.await?;
 
 	if let Some(retry_pressed) = response
-		.message()
-		.await?
 		.await_component_interaction(ctx)
 		.filter(move |mci: &ComponentInteraction| mci.data.custom_id == custom_id)
 		.timeout(std::time::Duration::from_secs(600))
@@ -336,77 +957,364 @@ pub async fn send_reply(
 		// If timed out, just remove the button
 		// Errors are ignored in case the reply was deleted
 		let _ = response
-			// TODO: Add code to remove button
-			.edit(ctx, poise::CreateReply::default())
+			.clone()
+			.edit(ctx, serenity::EditMessage::new().components(Vec::new()))
 			.await;
 	}
 
 	Ok(())
 }
 
-// This function must not break when provided non-formatted text with messed up formatting: rustfmt
-// may not be installed on the host's computer!
-pub fn strip_fn_main_boilerplate_from_formatted(text: &str) -> String {
-	// Remove the fn main boilerplate
-	let prefix = "fn main() {";
-	let postfix = "}";
+/// Applies `wrap` and `tail` to output text. Done the same way for every kind of reply, so the
+/// flags don't silently stop working with `image` or `embed`
+fn shape_output(text: &str, flags: &api::CommandFlags) -> String {
+	let mut text = Cow::Borrowed(text);
+	// Wrapping first, so `tail` counts the lines as shown and its note isn't wrapped
+	if let Some(width) = flags.wrap {
+		text = wrap_lines(&text, width.get()).into();
+	}
+	if let Some(tail) = flags.tail {
+		text = tail_lines(&text, tail.get()).into();
+	}
+	text.into_owned()
+}
 
-	let text = match (text.find(prefix), text.rfind(postfix)) {
-		(Some(prefix_pos), Some(postfix_pos)) => text
-			.get((prefix_pos + prefix.len())..postfix_pos)
-			.unwrap_or(text),
-		_ => text,
-	};
-	let text = text.trim();
+/// stdout and stderr as one text, shaped by [`shape_output`] and preceded by the toolchain for
+/// `showtoolchain=true`
+fn merged_output(result: &api::PlayResult, flags: &api::CommandFlags) -> String {
+	let output = crate::helpers::merge_output_and_errors(&result.stdout, &result.stderr);
+	let output = shape_output(&output, flags);
+	if flags.showtoolchain {
+		flags.toolchain_comment() + &output
+	} else {
+		output
+	}
+}
 
-	// Revert the indent introduced by rustfmt
-	let mut output = String::new();
-	for line in text.lines() {
-		output.push_str(line.strip_prefix("    ").unwrap_or(line));
-		output.push('\n');
+/// Shares the code on the playground and returns the link to it, for `qr=true`
+async fn playground_url(
+	ctx: Context<'_>,
+	code: &str,
+	flags: &api::CommandFlags,
+) -> Result<String, Error> {
+	let config = &ctx.data().config;
+	if config.no_gist {
+		anyhow::bail!("gists are disabled");
 	}
-	output
+	let gist_id = post_shared_gist(ctx, code, flags).await?;
+	Ok(api::url_from_gist(config, flags, &gist_id))
 }
 
-/// Split stderr into compiler output and program stderr output and format the two nicely
-///
-/// If the program doesn't compile, the compiler output is returned. If it did compile and run,
-/// compiler output (i.e. warnings) is shown only when `show_compiler_warnings` is true.
-pub fn format_play_eval_stderr(stderr: &str, show_compiler_warnings: bool) -> String {
-	// Extract core compiler output and remove boilerplate lines from top and bottom
-	let compiler_output = extract_relevant_lines(
-		stderr,
-		&["Compiling playground"],
-		&[
-			"warning emitted",
-			"warnings emitted",
-			"warning: `playground` (bin \"playground\") generated",
-			"warning: `playground` (lib) generated",
-			"error: could not compile",
-			"error: aborting",
-			"Finished ",
-		],
-	);
+/// The QR code of the playground link as an attachment, and the footer with the link appended
+/// as text too, for those who can't scan the QR code
+fn qr_code_attachment(
+	footer: &str,
+	url: &str,
+) -> Result<(String, serenity::CreateAttachment), Error> {
+	let png = qr::render_qr_png(url)?;
+	let footer = match footer.trim_end() {
+		"" => format!("playground: <{url}>"),
+		footer => format!("{footer}\nplayground: <{url}>"),
+	};
+	Ok((
+		footer,
+		serenity::CreateAttachment::bytes(png, "playground.png"),
+	))
+}
 
-	// If the program actually ran, compose compiler output and program stderr
-	// Using "Finished " here instead of "Running `target" because this method is also used by
-	// e.g. -Zunpretty=XXX family commands which don't actually run anything
-	if stderr.contains("Finished ") {
-		// Program successfully compiled, so compiler output will be just warnings
-		let program_stderr = extract_relevant_lines(stderr, &["Finished ", "Running `target"], &[]);
+/// Why the playground stopped the program early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KillReason {
+	Timeout,
+	OutOfMemory,
+	/// Killed by a signal without any sign of the cause
+	Killed,
+}
 
-		if show_compiler_warnings {
-			// Concatenate compiler output and program stderr with a newline
-			match (compiler_output, program_stderr) {
-				("", "") => String::new(),
-				(warnings, "") => warnings.to_owned(),
-				("", stderr) => stderr.to_owned(),
-				(warnings, stderr) => format!("{warnings}\n{stderr}"),
-			}
-		} else {
-			program_stderr.to_owned()
+impl KillReason {
+	/// Note shown after the output
+	fn note(self) -> &'static str {
+		match self {
+			KillReason::Timeout => "Playground timeout detected",
+			KillReason::OutOfMemory => "Your program ran out of memory and was killed.",
+			KillReason::Killed => "Your program was killed.",
 		}
-		.replace('`', "\u{200b}`")
+	}
+}
+
+/// Tells a timeout apart from hitting the playground's memory limit. Running out of memory is
+/// only reported with evidence: the abort message of a failed allocation, or the kernel's
+/// message for a cgroup OOM kill. A timeout ends in SIGKILL with the shell reporting the
+/// `timeout --signal=KILL` wrapper as `Killed` and exiting with status 137. Any other SIGKILL
+/// could have many causes, so it's reported without guessing one
+fn kill_reason(result: &api::PlayResult) -> Option<KillReason> {
+	let lines = || result.stdout.lines().chain(result.stderr.lines());
+
+	let out_of_memory = lines().any(|line| {
+		(line.starts_with("memory allocation of ") && line.ends_with(" bytes failed"))
+			|| line.contains("Memory cgroup out of memory")
+	});
+	if out_of_memory {
+		return Some(KillReason::OutOfMemory);
+	}
+
+	let wrapper_killed =
+		lines().any(|line| line.contains("Killed") && line.contains("timeout --signal=KILL"));
+	let exit_detail = result.exit_detail.as_str();
+	// Older playground responses don't say how the program exited
+	let timeout_status = exit_detail.is_empty() || exit_detail.contains("137");
+	let sigkilled = exit_detail.contains("SIGKILL") || exit_detail.contains("signal: 9");
+
+	if wrapper_killed && timeout_status {
+		Some(KillReason::Timeout)
+	} else if sigkilled || wrapper_killed {
+		Some(KillReason::Killed)
+	} else {
+		None
+	}
+}
+
+/// Sends the result as an embed with separate stdout and stderr fields, for `embed=true`
+async fn send_embed_reply(
+	ctx: Context<'_>,
+	result: &api::PlayResult,
+	code: &str,
+	flags: &api::CommandFlags,
+	flag_parse_errors: &str,
+	footer: &str,
+) -> Result<(), Error> {
+	// Discord rejects embed fields longer than this
+	const MAX_FIELD_LENGTH: usize = 1024;
+
+	let mut embed = serenity::CreateEmbed::new()
+		.title(if result.success { "Success" } else { "Failure" })
+		.footer(serenity::CreateEmbedFooter::new(flags.toolchain_summary()))
+		.color(crate::types::EMBED_COLOR);
+
+	let description = format!("{flag_parse_errors}{footer}");
+	if !description.trim().is_empty() {
+		embed = embed.description(description);
+	}
+
+	// Only created once, even if both fields overflow
+	let mut too_large_message = None;
+	let mut has_output = false;
+	// `showtoolchain` isn't needed, the embed footer always shows the toolchain
+	for (name, text) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+		let text = shape_output(text, flags);
+		let text = text.trim();
+		if text.is_empty() {
+			continue;
+		}
+		has_output = true;
+
+		let mut value = format!("```rust\n{text}\n```");
+		if value.chars().count() > MAX_FIELD_LENGTH {
+			if too_large_message.is_none() {
+				too_large_message = Some(output_too_large_message(ctx, code, flags).await);
+			}
+			value.clone_from(too_large_message.as_ref().unwrap());
+		}
+		embed = embed.field(name, value, false);
+	}
+	if !has_output {
+		embed = embed.field("Output", "_no output_", false);
+	}
+
+	send_result(
+		ctx,
+		flags,
+		poise::CreateReply::default()
+			.embed(embed)
+			.ephemeral(flags.ephemeral),
+	)
+	.await?;
+	Ok(())
+}
+
+/// Sends a playground result and pins it for `pin=true`. For `safe=true` the reply is sent with
+/// link previews suppressed from the start, instead of flashing them until an edit hides them.
+/// poise's `CreateReply` can't carry message flags, so those replies go through serenity directly.
+async fn send_result(
+	ctx: Context<'_>,
+	flags: &api::CommandFlags,
+	reply: poise::CreateReply,
+) -> Result<serenity::Message, Error> {
+	let message = if flags.safe {
+		send_without_embeds(ctx, reply).await?
+	} else {
+		ctx.send(reply).await?.into_message().await?
+	};
+
+	// `restrict_moderator_flags` only leaves `pin` on for moderators. If the bot itself lacks the
+	// permission the flag is ignored, so this never fails the command
+	if flags.pin {
+		if let Err(e) = message.pin(ctx).await {
+			tracing::warn!("Couldn't pin playground result: {}", e);
+		}
+	}
+	Ok(message)
+}
+
+/// Sends the reply like [`poise::send_reply`] does, but with `SUPPRESS_EMBEDS` set
+async fn send_without_embeds(
+	ctx: Context<'_>,
+	reply: poise::CreateReply,
+) -> Result<serenity::Message, Error> {
+	let reply = ctx.reply_builder(reply);
+	match ctx {
+		Context::Prefix(ctx) => {
+			let edit_tracker = &ctx.framework.options().prefix_options.edit_tracker;
+			// Like poise's own replies, edit the placeholder or the result of a previous run of
+			// the same message instead of replying again
+			let existing_response = edit_tracker.as_ref().and_then(|tracker| {
+				tracker
+					.read()
+					.unwrap()
+					.find_bot_response(ctx.msg.id)
+					.cloned()
+			});
+			let message = if let Some(mut response) = existing_response {
+				// Reset the message, so nothing of the previous contents is left over
+				let builder = serenity::EditMessage::new()
+					.content("")
+					.embeds(Vec::new())
+					.components(Vec::new())
+					.remove_all_attachments()
+					.flags(serenity::MessageFlags::SUPPRESS_EMBEDS);
+				response
+					.edit(ctx.serenity_context, reply.to_prefix_edit(builder))
+					.await?;
+				response
+			} else {
+				let builder = reply
+					.to_prefix(ctx.msg.into())
+					.flags(serenity::MessageFlags::SUPPRESS_EMBEDS);
+				ctx.msg
+					.channel_id
+					.send_message(ctx.serenity_context, builder)
+					.await?
+			};
+			// So the reply is deleted with the invocation and edited on the next run, like other
+			// replies
+			if let Some(edit_tracker) = edit_tracker {
+				edit_tracker.write().unwrap().set_bot_response(
+					ctx.msg,
+					message.clone(),
+					ctx.command.track_deletion,
+				);
+			}
+			Ok(message)
+		}
+		Context::Application(ctx) => {
+			let sent_initial_response = &ctx.has_sent_initial_response;
+			if sent_initial_response.load(std::sync::atomic::Ordering::SeqCst) {
+				let builder = reply.to_slash_followup_response(
+					serenity::CreateInteractionResponseFollowup::new()
+						.flags(serenity::MessageFlags::SUPPRESS_EMBEDS),
+				);
+				Ok(ctx
+					.interaction
+					.create_followup(ctx.serenity_context, builder)
+					.await?)
+			} else {
+				let builder = reply.to_slash_initial_response(
+					serenity::CreateInteractionResponseMessage::new()
+						.flags(serenity::InteractionResponseFlags::SUPPRESS_EMBEDS),
+				);
+				ctx.interaction
+					.create_response(
+						ctx.serenity_context,
+						serenity::CreateInteractionResponse::Message(builder),
+					)
+					.await?;
+				sent_initial_response.store(true, std::sync::atomic::Ordering::SeqCst);
+				Ok(ctx.interaction.get_response(ctx.serenity_context).await?)
+			}
+		}
+	}
+}
+
+/// Breaks up `@everyone`, `@here` and role mentions with a zero-width space, so they don't ping
+/// anyone when the text is reposted
+fn neutralize_mentions(text: &str) -> String {
+	text.replace("@everyone", "@\u{200b}everyone")
+		.replace("@here", "@\u{200b}here")
+		.replace("<@&", "<@\u{200b}&")
+}
+
+/// [`neutralize_mentions`] for `safe=true`, otherwise the text as is
+fn neutralize_mentions_if_safe<'a>(flags: &api::CommandFlags, text: &'a str) -> Cow<'a, str> {
+	if flags.safe {
+		neutralize_mentions(text).into()
+	} else {
+		text.into()
+	}
+}
+
+// This function must not break when provided non-formatted text with messed up formatting: rustfmt
+// may not be installed on the host's computer!
+pub fn strip_fn_main_boilerplate_from_formatted(text: &str) -> String {
+	// Remove the fn main boilerplate
+	let prefix = "fn main() {";
+	let postfix = "}";
+
+	let text = match (text.find(prefix), text.rfind(postfix)) {
+		(Some(prefix_pos), Some(postfix_pos)) => text
+			.get((prefix_pos + prefix.len())..postfix_pos)
+			.unwrap_or(text),
+		_ => text,
+	};
+	let text = text.trim();
+
+	// Revert the indent introduced by rustfmt
+	let mut output = String::new();
+	for line in text.lines() {
+		output.push_str(line.strip_prefix("    ").unwrap_or(line));
+		output.push('\n');
+	}
+	output
+}
+
+/// Split stderr into compiler output and program stderr output and format the two nicely
+///
+/// If the program doesn't compile, the compiler output is returned. If it did compile and run,
+/// compiler output (i.e. warnings) is shown only when `show_compiler_warnings` is true.
+pub fn format_play_eval_stderr(stderr: &str, show_compiler_warnings: bool) -> String {
+	// Extract core compiler output and remove boilerplate lines from top and bottom
+	let compiler_output = extract_relevant_lines(
+		stderr,
+		&["Compiling playground"],
+		&[
+			"warning emitted",
+			"warnings emitted",
+			"warning: `playground` (bin \"playground\") generated",
+			"warning: `playground` (lib) generated",
+			"error: could not compile",
+			"error: aborting",
+			"Finished ",
+		],
+	);
+
+	// If the program actually ran, compose compiler output and program stderr
+	// Using "Finished " here instead of "Running `target" because this method is also used by
+	// e.g. -Zunpretty=XXX family commands which don't actually run anything
+	if stderr.contains("Finished ") {
+		// Program successfully compiled, so compiler output will be just warnings
+		let program_stderr = extract_relevant_lines(stderr, &["Finished ", "Running `target"], &[]);
+
+		if show_compiler_warnings {
+			// Concatenate compiler output and program stderr with a newline
+			match (compiler_output, program_stderr) {
+				("", "") => String::new(),
+				(warnings, "") => warnings.to_owned(),
+				("", stderr) => stderr.to_owned(),
+				(warnings, stderr) => format!("{warnings}\n{stderr}"),
+			}
+		} else {
+			program_stderr.to_owned()
+		}
+		.replace('`', "\u{200b}`")
 	} else {
 		// Program didn't get to run, so there must be an error, so we yield the compiler output
 		// regardless of whether warn is enabled or not
@@ -414,6 +1322,755 @@ pub fn format_play_eval_stderr(stderr: &str, show_compiler_warnings: bool) -> St
 	}
 }
 
+/// Awaits `future`, running `on_deadline` first if it doesn't finish within `deadline`
+pub async fn await_with_deadline<T>(
+	future: impl std::future::Future<Output = T>,
+	deadline: std::time::Duration,
+	on_deadline: impl std::future::Future<Output = ()>,
+) -> T {
+	let mut future = std::pin::pin!(future);
+	if let Ok(output) = tokio::time::timeout(deadline, &mut future).await {
+		return output;
+	}
+	on_deadline.await;
+	future.await
+}
+
+/// Number of instructions in filtered assembly, i.e. all lines except labels, directives and
+/// comments
+pub fn count_instructions(asm: &str) -> usize {
+	asm.lines()
+		.map(str::trim)
+		.filter(|line| {
+			!line.is_empty()
+				&& !line.ends_with(':')
+				&& !line.starts_with('.')
+				&& !line.starts_with(';')
+				&& !line.starts_with('#')
+		})
+		.count()
+}
+
+/// proc-macro2 remembers the source of everything parsed on a thread, so that [`super::inject`] can look
+/// up where in the code a token is. Everything that parses code calls this once it's done with
+/// the positions, so the sources don't pile up
+pub fn forget_parsed_sources() {
+	proc_macro2::extra::invalidate_current_thread_spans();
+}
+
+/// Tokenizes the code, which fails on unbalanced brackets or unterminated strings, for example
+pub fn tokenize(code: &str) -> Option<proc_macro2::TokenStream> {
+	let tokens = code.parse().ok();
+	forget_parsed_sources();
+	tokens
+}
+
+/// Number of `unsafe` keywords in the code, i.e. unsafe blocks, functions, impls, traits and
+/// extern blocks. Occurrences in strings and comments aren't tokens, so they're not counted.
+/// Returns `None` if the code can't be tokenized, e.g. due to unbalanced brackets
+pub fn count_unsafe(code: &str) -> Option<usize> {
+	fn count(tokens: proc_macro2::TokenStream) -> usize {
+		tokens
+			.into_iter()
+			.map(|token| match token {
+				proc_macro2::TokenTree::Ident(ident) => usize::from(ident == "unsafe"),
+				proc_macro2::TokenTree::Group(group) => count(group.stream()),
+				_ => 0,
+			})
+			.sum()
+	}
+
+	tokenize(code).map(count)
+}
+
+/// Whether the code refers to networking APIs, like `std::net`, `TcpStream` or HTTP client crates.
+/// Strings and comments are ignored
+pub fn uses_network(code: &str) -> bool {
+	/// Types and crates that only exist to talk to the network
+	const NETWORK_IDENTS: &[&str] = &[
+		"TcpStream",
+		"TcpListener",
+		"UdpSocket",
+		"reqwest",
+		"ureq",
+		"hyper",
+		"surf",
+		"isahc",
+		"attohttpc",
+	];
+
+	fn flatten(tokens: proc_macro2::TokenStream, idents: &mut Vec<String>) {
+		for token in tokens {
+			match token {
+				proc_macro2::TokenTree::Ident(ident) => idents.push(ident.to_string()),
+				proc_macro2::TokenTree::Group(group) => flatten(group.stream(), idents),
+				// Path separators are irrelevant for telling `std::net` apart from other `net`s
+				proc_macro2::TokenTree::Punct(_) | proc_macro2::TokenTree::Literal(_) => {}
+			}
+		}
+	}
+
+	let Some(tokens) = tokenize(code) else {
+		return false;
+	};
+	let mut idents = Vec::new();
+	flatten(tokens, &mut idents);
+
+	idents
+		.iter()
+		.any(|ident| NETWORK_IDENTS.contains(&ident.as_str()))
+		|| idents.windows(2).any(|pair| pair == ["std", "net"])
+}
+
+/// Whether the tokens start with `::`
+fn is_path_separator(tokens: &[proc_macro2::TokenTree]) -> bool {
+	matches!(
+		tokens,
+		[proc_macro2::TokenTree::Punct(first), proc_macro2::TokenTree::Punct(second), ..]
+			if first.as_char() == ':'
+				&& first.spacing() == proc_macro2::Spacing::Joint
+				&& second.as_char() == ':'
+	)
+}
+
+/// Names the code may refer to crates by, via `extern crate`, `use` or a path starting with the
+/// crate name. Strings and comments are ignored, and so is code that can't be tokenized, as it
+/// won't compile anyways
+fn crate_roots(code: &str) -> Vec<String> {
+	/// Adds the root of each path in a `use` tree, e.g. `a` and `b` for `{a::x, ::b}`
+	fn collect_use_roots(tokens: &[proc_macro2::TokenTree], roots: &mut Vec<String>) {
+		let tokens = if is_path_separator(tokens) {
+			&tokens[2..]
+		} else {
+			tokens
+		};
+		match tokens.first() {
+			Some(proc_macro2::TokenTree::Ident(ident)) => roots.push(ident.to_string()),
+			Some(proc_macro2::TokenTree::Group(group))
+				if group.delimiter() == proc_macro2::Delimiter::Brace =>
+			{
+				let inner = group.stream().into_iter().collect::<Vec<_>>();
+				for tree in inner.split(
+					|token| matches!(token, proc_macro2::TokenTree::Punct(p) if p.as_char() == ','),
+				) {
+					collect_use_roots(tree, roots);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn collect_roots(tokens: proc_macro2::TokenStream, roots: &mut Vec<String>) {
+		let tokens = tokens.into_iter().collect::<Vec<_>>();
+		for (i, token) in tokens.iter().enumerate() {
+			match token {
+				proc_macro2::TokenTree::Ident(ident) if ident == "use" => {
+					collect_use_roots(&tokens[(i + 1)..], roots);
+				}
+				proc_macro2::TokenTree::Ident(ident) if ident == "extern" => {
+					if let [proc_macro2::TokenTree::Ident(krate), proc_macro2::TokenTree::Ident(name), ..] =
+						&tokens[(i + 1)..]
+					{
+						if krate == "crate" {
+							roots.push(name.to_string());
+						}
+					}
+				}
+				// The first segment of a path like `rand::random()`, but not `foo::rand::x`
+				proc_macro2::TokenTree::Ident(ident)
+					if is_path_separator(&tokens[(i + 1)..])
+						&& !(i >= 2 && is_path_separator(&tokens[(i - 2)..])) =>
+				{
+					roots.push(ident.to_string());
+				}
+				proc_macro2::TokenTree::Group(group) => collect_roots(group.stream(), roots),
+				_ => {}
+			}
+		}
+	}
+
+	let Some(tokens) = tokenize(code) else {
+		return Vec::new();
+	};
+	let mut roots = Vec::new();
+	collect_roots(tokens, &mut roots);
+	roots
+}
+
+/// The crates out of `forbidden` which the code refers to, see [`crate_roots`]
+pub fn find_forbidden_crates<'a>(code: &str, forbidden: &'a [String]) -> Vec<&'a str> {
+	let roots = crate_roots(code);
+	forbidden
+		.iter()
+		.filter(|name| roots.contains(name))
+		.map(String::as_str)
+		.collect()
+}
+
+/// A `Cargo.toml` for running the code locally, depending on those of the playground's crates the
+/// code refers to, see [`crate_roots`]
+pub fn cargo_manifest(code: &str, edition: api::Edition, available: &[api::CrateInfo]) -> String {
+	let roots = crate_roots(code);
+	let mut manifest = format!(
+		"[package]\nname = \"playground\"\nversion = \"0.1.0\"\nedition = \"{}\"\n\n[dependencies]\n",
+		edition.name()
+	);
+	// Code refers to crates by their library name, which has underscores instead of dashes
+	for krate in available.iter().filter(|krate| roots.contains(&krate.id)) {
+		let _ = writeln!(manifest, "{} = \"{}\"", krate.name, krate.version);
+	}
+	manifest
+}
+
+/// Paths of blocking std calls, like `std::thread::sleep` or `fs::read`, made inside `async`
+/// functions and blocks. This only looks at the paths as written, so calls via a `use`d function
+/// name are missed. Closures passed to `spawn_blocking` or `thread::spawn` don't count, as that's
+/// how blocking code is supposed to be run. Strings and comments are ignored
+pub fn find_blocking_in_async(code: &str) -> Vec<String> {
+	/// Last two segments of paths to blocking std functions
+	const BLOCKING_CALLS: &[[&str; 2]] = &[
+		["thread", "sleep"],
+		["fs", "read"],
+		["fs", "read_to_string"],
+		["fs", "read_dir"],
+		["fs", "write"],
+		["fs", "copy"],
+		["fs", "remove_file"],
+		["fs", "create_dir_all"],
+		["File", "open"],
+		["File", "create"],
+		["io", "stdin"],
+		["TcpStream", "connect"],
+		["TcpListener", "bind"],
+	];
+	/// Crates with async functions of the same names
+	const ASYNC_CRATES: &[&str] = &["tokio", "async_std", "smol"];
+
+	fn is_blocking(path: &[String]) -> bool {
+		path.len() >= 2
+			&& BLOCKING_CALLS.contains(&[&path[path.len() - 2], &path[path.len() - 1]])
+			&& !path
+				.iter()
+				.any(|segment| ASYNC_CRATES.contains(&segment.as_str()))
+	}
+
+	fn is_spawn(path: &[String]) -> bool {
+		path.last().is_some_and(|name| name == "spawn_blocking")
+			|| path.ends_with(&["thread".to_owned(), "spawn".to_owned()])
+	}
+
+	fn collect(tokens: proc_macro2::TokenStream, in_async: bool, found: &mut Vec<String>) {
+		let tokens = tokens.into_iter().collect::<Vec<_>>();
+		let mut path = Vec::new();
+		// Set by `async`, the next braced group is the body of the async fn, block or closure
+		let mut async_pending = false;
+		let mut i = 0;
+		while i < tokens.len() {
+			match &tokens[i] {
+				proc_macro2::TokenTree::Ident(ident) => {
+					if ident == "async" {
+						async_pending = true;
+					}
+					path.push(ident.to_string());
+					if is_path_separator(&tokens[(i + 1)..]) {
+						i += 3;
+						continue;
+					}
+					if in_async && is_blocking(&path) {
+						found.push(path.join("::"));
+					}
+					if let (true, Some(proc_macro2::TokenTree::Group(args))) =
+						(is_spawn(&path), tokens.get(i + 1))
+					{
+						collect(args.stream(), false, found);
+						i += 1;
+					}
+					path.clear();
+				}
+				proc_macro2::TokenTree::Group(group) => {
+					let is_async_body =
+						async_pending && group.delimiter() == proc_macro2::Delimiter::Brace;
+					if is_async_body {
+						async_pending = false;
+					}
+					collect(group.stream(), in_async || is_async_body, found);
+					path.clear();
+				}
+				proc_macro2::TokenTree::Punct(punct) => {
+					// A bodyless `async fn` in a trait
+					if punct.as_char() == ';' {
+						async_pending = false;
+					}
+					path.clear();
+				}
+				proc_macro2::TokenTree::Literal(_) => path.clear(),
+			}
+			i += 1;
+		}
+	}
+
+	let Some(tokens) = tokenize(code) else {
+		return Vec::new();
+	};
+	let mut found = Vec::new();
+	collect(tokens, false, &mut found);
+	found.dedup();
+	found
+}
+
+/// Describes the size of the playground's wasm output for `binsize=true`. The playground hands
+/// back the WebAssembly text format rather than the binary module, which is several times larger
+/// than the binary, so it's reported as such, along with the number of functions in the module
+pub fn describe_wasm_size(wat: &str) -> String {
+	let functions = wat
+		.lines()
+		.filter(|line| line.trim_start().starts_with("(func "))
+		.count();
+	format!(
+		"wasm text format ~{} KiB, {functions} functions (the binary is several times smaller)",
+		(wat.len() + 512) / 1024
+	)
+}
+
+/// Describes how much smaller (or larger) the release build's assembly is than the debug build's
+pub fn describe_size_reduction(debug_instructions: usize, release_instructions: usize) -> String {
+	let counts = format!("{debug_instructions} → {release_instructions} instructions");
+	if debug_instructions == 0 {
+		return format!("no assembly to compare ({counts})");
+	}
+
+	#[allow(clippy::cast_precision_loss)] // instruction counts are nowhere near 2^52
+	let change = (1.0 - release_instructions as f64 / debug_instructions as f64) * 100.0;
+	if change >= 0.0 {
+		format!("release is {change:.0}% smaller assembly than debug ({counts})")
+	} else {
+		format!(
+			"release is {:.0}% larger assembly than debug ({counts})",
+			-change
+		)
+	}
+}
+
+/// Strips the cargo boilerplate from Miri's output, leaving the program's stderr and Miri's
+/// diagnostics
+pub fn format_miri_stderr(stderr: &str) -> String {
+	extract_relevant_lines(stderr, &["Running `/playground"], &["error: aborting"]).to_owned()
+}
+
+/// Summarizes the warnings in the compiler output by lint, like
+/// `unused_variables (3), dead_code (1)`. Returns `None` if there are no warnings.
+///
+/// rustc only names the lint (`#[warn(unused_variables)]` on by default) at its first warning, so
+/// later warnings are attributed by their message, ignoring the parts in backticks.
+pub fn group_warnings_by_lint(stderr: &str) -> Option<String> {
+	fn message_shape(message: &str) -> String {
+		message.split('`').step_by(2).collect::<Vec<_>>().join("``")
+	}
+
+	// Counts per lint, in order of first appearance
+	let mut counts: Vec<(String, usize)> = Vec::new();
+	let mut lint_of_shape = std::collections::HashMap::new();
+
+	let mut diagnostics = Vec::new();
+	for line in stderr.lines() {
+		if let Some(message) = line.strip_prefix("warning: ") {
+			// Skip the "N warnings emitted" and cargo's "`crate` (bin ...) generated N warnings"
+			// summaries
+			let is_summary = message.ends_with(" emitted")
+				|| (message.contains(") generated ") && message.contains(" warning"));
+			if !is_summary {
+				diagnostics.push((message_shape(message), None));
+			}
+		} else if line.starts_with("error") {
+			diagnostics.push((String::new(), None));
+		} else if let Some((_, lint)) = line.split_once("#[warn(") {
+			if let (Some(diagnostic), Some((lint, _))) =
+				(diagnostics.last_mut(), lint.split_once(')'))
+			{
+				diagnostic.1.get_or_insert_with(|| lint.to_owned());
+			}
+		}
+	}
+
+	for (shape, lint) in diagnostics {
+		if shape.is_empty() {
+			continue;
+		}
+		let lint = match lint {
+			Some(lint) => {
+				lint_of_shape.insert(shape, lint.clone());
+				lint
+			}
+			None => lint_of_shape.get(&shape).cloned().unwrap_or(shape),
+		};
+		match counts.iter_mut().find(|(name, _)| *name == lint) {
+			Some((_, count)) => *count += 1,
+			None => counts.push((lint, 1)),
+		}
+	}
+
+	if counts.is_empty() {
+		return None;
+	}
+	let summary = counts
+		.iter()
+		.map(|(lint, count)| format!("{lint} ({count})"))
+		.collect::<Vec<_>>()
+		.join(", ");
+	Some(format!("warnings: {summary}\n"))
+}
+
+/// Whether rustc crashed with an internal compiler error (ICE) instead of compiling the code
+pub fn is_internal_compiler_error(stderr: &str) -> bool {
+	stderr.contains("error: internal compiler error")
+}
+
+/// If the program panicked, rewrites the panic in its (formatted) stderr into a short
+/// `{subject} panicked at location:` line followed by the panic message, dropping the backtrace
+/// hint. Returns `None` if there was no panic.
+pub fn describe_panic(stderr: &str, subject: &str) -> Option<String> {
+	let mut panicked = false;
+	let mut output = String::new();
+	for line in stderr.lines() {
+		if let Some((_, location)) = line
+			.strip_prefix("thread '")
+			.and_then(|line| line.split_once("' panicked at "))
+		{
+			panicked = true;
+			let _ = writeln!(output, "{subject} panicked at {location}");
+		} else if !(line.starts_with("note: run with ") && line.contains("RUST_BACKTRACE")) {
+			output += line;
+			output += "\n";
+		}
+	}
+	panicked.then_some(output)
+}
+
+/// Joins multiple multiline texts into one, each preceded by a `--- label ---` line, so the
+/// results of several runs can be shown in a single code block
+pub fn labeled_sections<'a>(sections: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+	let mut output = String::new();
+	for (label, text) in sections {
+		output += "--- ";
+		output += label;
+		output += " ---\n";
+		output += text.trim_end_matches('\n');
+		output += "\n";
+	}
+	output
+}
+
+/// Applies the suggestions rustc shows inline after the underlined code, like
+/// ``^^^^ help: replace it with: `x` ``, and returns the rewritten code along with how many were
+/// applied. Line numbers in `stderr` must refer to `code`.
+///
+/// The playground only gives us the human readable output, in which longer suggestions are shown
+/// as diffs, so those are skipped. So are lines which aren't displayed verbatim.
+pub fn apply_inline_suggestions(code: &str, stderr: &str) -> (String, usize) {
+	let mut lines = code.lines().map(str::to_owned).collect::<Vec<_>>();
+
+	// Line index, byte range within the line and replacement of each suggestion
+	let mut suggestions = Vec::new();
+	let mut shown_line = None;
+	for line in stderr.lines() {
+		let Some((gutter, rest)) = line.split_once('|') else {
+			continue;
+		};
+		let rest = rest.strip_prefix(' ').unwrap_or(rest);
+		if let Ok(line_number) = gutter.trim().parse::<usize>() {
+			shown_line = Some((line_number, rest));
+			continue;
+		}
+		let Some((line_number, shown)) = shown_line else {
+			continue;
+		};
+		if !gutter.trim().is_empty() {
+			continue;
+		}
+
+		let column = rest.len() - rest.trim_start_matches(' ').len();
+		let carets = rest[column..].len() - rest[column..].trim_start_matches('^').len();
+		let Some((_, replacement)) = rest[(column + carets)..]
+			.strip_prefix(" help: ")
+			.and_then(|help| help.split_once(": `"))
+		else {
+			continue;
+		};
+		let Some(replacement) = replacement.strip_suffix('`') else {
+			continue;
+		};
+
+		// Tabs are shown as four spaces, other than that the line must match exactly
+		let Some(original) = line_number.checked_sub(1).and_then(|i| code.lines().nth(i)) else {
+			continue;
+		};
+		if carets == 0 || !original.is_ascii() || original.replace('\t', "    ") != shown {
+			continue;
+		}
+		let byte_offset = |shown_column: usize| {
+			let mut current = 0;
+			for (i, c) in original.char_indices() {
+				if current == shown_column {
+					return Some(i);
+				}
+				current += if c == '\t' { 4 } else { 1 };
+			}
+			(current == shown_column).then_some(original.len())
+		};
+		if let (Some(start), Some(end)) = (byte_offset(column), byte_offset(column + carets)) {
+			suggestions.push((line_number - 1, start, end, replacement.to_owned()));
+		}
+	}
+
+	// Applied back to front, so earlier ranges stay valid. Overlapping ones are skipped
+	suggestions.sort();
+	suggestions.dedup();
+	let mut applied = 0;
+	let mut next_start = None;
+	for (line, start, end, replacement) in suggestions.into_iter().rev() {
+		if next_start.is_some_and(|(next_line, next_start)| next_line == line && end > next_start) {
+			continue;
+		}
+		lines[line].replace_range(start..end, &replacement);
+		next_start = Some((line, start));
+		applied += 1;
+	}
+
+	let mut rewritten = lines.join("\n");
+	if code.ends_with('\n') {
+		rewritten.push('\n');
+	}
+	(rewritten, applied)
+}
+
+/// Whether Clippy's output contains any lints or errors. Clippy succeeds even if it warns, so the
+/// result's `success` alone doesn't tell whether the code is clippy-clean
+pub fn has_clippy_diagnostics(stderr: &str) -> bool {
+	stderr
+		.lines()
+		.any(|line| line.starts_with("warning") || line.starts_with("error"))
+}
+
+/// One line summary of `style=true`, like `✅ clippy clean, ❌ not rustfmt-formatted (run ?fmt)`.
+/// `formatted` is `None` if rustfmt failed to parse the code
+pub fn style_summary(clippy_clean: bool, formatted: Option<bool>) -> String {
+	let clippy = if clippy_clean {
+		"✅ clippy clean"
+	} else {
+		"❌ not clippy clean (run ?clippy)"
+	};
+	let rustfmt = match formatted {
+		Some(true) => "✅ rustfmt-formatted",
+		Some(false) => "❌ not rustfmt-formatted (run ?fmt)",
+		None => "❌ rustfmt couldn't parse the code",
+	};
+	format!("{clippy}, {rustfmt}")
+}
+
+/// Explains how running the same code in debug and release mode differed, for `panicdemo=true`.
+/// Expects the results' stderr to be formatted already
+pub fn describe_mode_divergence(debug: &api::PlayResult, release: &api::PlayResult) -> String {
+	let overflowed = |result: &api::PlayResult| result.stderr.contains("with overflow");
+	match (debug.success, release.success) {
+		(false, true) if overflowed(debug) => "debug panicked on an arithmetic overflow, while \
+			release silently wrapped around, because overflow checks are only on in debug mode by \
+			default"
+			.to_owned(),
+		(false, true) => "debug failed, but release succeeded".to_owned(),
+		(true, false) => "release failed, but debug succeeded".to_owned(),
+		(false, false) => "both debug and release failed".to_owned(),
+		(true, true) if debug.stdout != release.stdout => "debug and release printed different \
+			output, which can be caused by overflows wrapping around in release mode, or by \
+			undefined behavior"
+			.to_owned(),
+		(true, true) => "debug and release behaved the same".to_owned(),
+	}
+}
+
+/// Compact overview of several runs of the same code, one `label ✅` or `label ❌ first error`
+/// line per run. Expects the results' stderr to be formatted already.
+pub fn outcome_matrix(rows: &[(&str, api::PlayResult)]) -> String {
+	let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+	let mut output = String::new();
+	for (label, result) in rows {
+		let _ = write!(output, "{label:label_width$} ");
+		if result.success {
+			output += "✅\n";
+		} else {
+			let first_error = result
+				.stderr
+				.lines()
+				.find(|line| line.starts_with("error"))
+				.or_else(|| result.stderr.lines().find(|line| !line.trim().is_empty()))
+				.unwrap_or("");
+			let _ = writeln!(output, "❌ {first_error}");
+		}
+	}
+	output
+}
+
+/// Keeps only the last `n` lines of the text, noting how many were dropped. Fewer lines are kept
+/// if they wouldn't fit into a Discord message, as the usual truncation would cut off the end,
+/// which is the part `tail=N` users care about.
+pub fn tail_lines(text: &str, n: usize) -> String {
+	// Leaves room for the flag errors, code fences and footer
+	const MAX_TAIL_LENGTH: usize = 1500;
+	// Same as the line limit of `crate::helpers::trim_text`, minus the omission note
+	const MAX_TAIL_LINES: usize = 44;
+
+	let lines = text.lines().collect::<Vec<_>>();
+	let mut kept = 0;
+	let mut length = 0;
+	for line in lines.iter().rev().take(n.min(MAX_TAIL_LINES)) {
+		length += line.len() + 1;
+		if length > MAX_TAIL_LENGTH {
+			break;
+		}
+		kept += 1;
+	}
+
+	let mut output = String::new();
+	let omitted = lines.len() - kept;
+	if omitted > 0 {
+		let _ = writeln!(output, "({omitted} earlier lines omitted)");
+	}
+	for line in &lines[omitted..] {
+		output += line;
+		output += "\n";
+	}
+	output
+}
+
+/// Breaks lines longer than `width` characters into several, for `wrap=N`
+pub fn wrap_lines(text: &str, width: usize) -> String {
+	let mut output = String::new();
+	for line in text.lines() {
+		let mut chars = line.chars().peekable();
+		loop {
+			output.extend(chars.by_ref().take(width));
+			output += "\n";
+			if chars.peek().is_none() {
+				break;
+			}
+		}
+	}
+	output
+}
+
+/// Histogram of the lengths of the lines in characters, in at most five ranges, with bars
+/// scaled to the most common range. Returns `None` if there are no lines.
+pub fn line_length_histogram(text: &str) -> Option<String> {
+	const MAX_BUCKETS: usize = 5;
+	const MAX_BAR_WIDTH: usize = 20;
+
+	let lengths = text
+		.lines()
+		.map(|line| line.chars().count())
+		.collect::<Vec<_>>();
+	let longest = *lengths.iter().max()?;
+	let bucket_width = (longest + 1).div_ceil(MAX_BUCKETS);
+	let mut counts = vec![0; (longest + 1).div_ceil(bucket_width)];
+	for length in lengths {
+		counts[length / bucket_width] += 1;
+	}
+
+	let labels = (0..counts.len())
+		.map(|i| {
+			let start = i * bucket_width;
+			format!("{start}-{}", start + bucket_width - 1)
+		})
+		.collect::<Vec<_>>();
+	let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+	let most = counts.iter().copied().max().unwrap_or(1);
+
+	let mut histogram = String::new();
+	for (label, count) in labels.iter().zip(counts) {
+		// Rounded up, so that ranges with any lines at all get a bar
+		let bar = "█".repeat((count * MAX_BAR_WIDTH).div_ceil(most));
+		let _ = writeln!(histogram, "{label:>label_width$} {bar} {count}");
+	}
+	Some(histogram)
+}
+
+/// Collapses runs of identical consecutive lines into a single `line (repeated N times)` line
+pub fn dedupe_lines(text: &str) -> String {
+	let mut output = String::new();
+	let mut lines = text.lines().peekable();
+	while let Some(line) = lines.next() {
+		let mut repetitions = 1;
+		while lines.next_if_eq(&line).is_some() {
+			repetitions += 1;
+		}
+
+		if repetitions == 1 {
+			let _ = writeln!(output, "{line}");
+		} else {
+			let _ = writeln!(output, "{line} (repeated {repetitions} times)");
+		}
+	}
+	output
+}
+
+/// Keeps only the first `max` diagnostics of the given kind (e.g. `"error"` or `"warning"`) and
+/// drops the rest. Returns the remaining text and the number of dropped diagnostics.
+///
+/// A diagnostic starts at a line beginning with its kind (`error: ...`, `error[E0382]: ...`) and
+/// spans all lines up to the next diagnostic of any kind. Text before the first diagnostic and
+/// diagnostics of other kinds are kept as-is.
+pub fn limit_diagnostics(text: &str, kind: &str, max: usize) -> (String, usize) {
+	fn diagnostic_kind(line: &str) -> Option<&str> {
+		["error", "warning"].into_iter().find(|kind| {
+			line.strip_prefix(kind)
+				.is_some_and(|rest| rest.starts_with(':') || rest.starts_with('['))
+		})
+	}
+
+	let mut output = String::new();
+	let mut seen = 0;
+	let mut omitted = 0;
+	let mut keeping = true;
+	for line in text.lines() {
+		if let Some(line_kind) = diagnostic_kind(line) {
+			keeping = if line_kind == kind {
+				seen += 1;
+				seen <= max
+			} else {
+				true
+			};
+			if !keeping {
+				omitted += 1;
+			}
+		}
+
+		if keeping {
+			output.push_str(line);
+			output.push('\n');
+		}
+	}
+
+	(output, omitted)
+}
+
+/// Keeps only the first `max` warnings in the playground's raw stderr, see [`limit_diagnostics`].
+/// Returns the remaining stderr and the number of dropped warnings. Cargo's summary and
+/// everything after it, like the program's own stderr, is left alone, as it'd otherwise be
+/// dropped along with the last warning
+pub fn limit_compiler_warnings(stderr: &str, max: usize) -> (String, usize) {
+	let mut compiler_output_len = 0;
+	for line in stderr.split_inclusive('\n') {
+		if line.starts_with("warning: `playground`") || line.trim_start().starts_with("Finished ") {
+			break;
+		}
+		compiler_output_len += line.len();
+	}
+
+	let (compiler_output, rest) = stderr.split_at(compiler_output_len);
+	let (mut limited, omitted) = limit_diagnostics(compiler_output, "warning", max);
+	limited += rest;
+	(limited, omitted)
+}
+
 pub fn stub_message(ctx: Context<'_>) -> String {
 	let mut stub_message = String::from("_Running code on playground..._\n");
 
@@ -430,3 +2087,731 @@ pub fn stub_message(ctx: Context<'_>) -> String {
 	stub_message.truncate(2000);
 	stub_message
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn wasm_size_is_reported_as_text_format() {
+		let wat = format!(
+			"(module\n  (type (;0;) (func))\n  (func $main (type 0)\n{}  )\n  (func $helper (type 0))\n)\n",
+			"    nop\n".repeat(300)
+		);
+		assert_eq!(
+			describe_wasm_size(&wat),
+			"wasm text format ~2 KiB, 2 functions (the binary is several times smaller)"
+		);
+		assert_eq!(
+			describe_wasm_size(""),
+			"wasm text format ~0 KiB, 0 functions (the binary is several times smaller)"
+		);
+	}
+
+	fn flags(args: &str) -> (api::CommandFlags, String) {
+		let mut parsed = poise::KeyValueArgs::default();
+		for arg in args.split_whitespace() {
+			let (key, value) = arg.split_once('=').unwrap();
+			parsed.0.insert(key.to_owned(), value.to_owned());
+		}
+		parse_flags(&Config::default(), parsed)
+	}
+
+	#[test]
+	fn moderator_flags_are_turned_off_for_others() {
+		let (mut parsed, _) = flags("pin=true webhook=true");
+		assert_eq!(
+			restrict_moderator_flags(&mut parsed, false),
+			"`pin` is only available to moderators\n`webhook` is only available to moderators\n"
+		);
+		assert!(!parsed.pin && !parsed.webhook);
+
+		let (mut parsed, _) = flags("pin=true webhook=true");
+		assert_eq!(restrict_moderator_flags(&mut parsed, true), "");
+		assert!(parsed.pin && parsed.webhook);
+
+		let (mut parsed, _) = flags("tail=3");
+		assert_eq!(restrict_moderator_flags(&mut parsed, false), "");
+	}
+
+	#[test]
+	fn crate_attributes_and_preceding_comments_are_hoisted() {
+		let code = "// enables the feature\n#![feature(never_type)]\n//! crate docs\nlet x = 1;\n";
+		assert_eq!(
+			hoise_crate_attributes(code, "fn main() {\n", "}\n"),
+			"// enables the feature\n#![feature(never_type)]\n//! crate docs\nfn main() {\nlet x = 1;\n}\n"
+		);
+
+		let code = "#![feature(\n\ttry_blocks,\n\tnever_type\n)]\nlet x = 1;\n";
+		assert_eq!(
+			hoise_crate_attributes(code, "fn main() {\n", "}\n"),
+			"#![feature(\ntry_blocks,\nnever_type\n)]\nfn main() {\nlet x = 1;\n}\n"
+		);
+	}
+
+	#[test]
+	fn comments_of_the_first_item_stay_with_it() {
+		let code = "#![allow(unused)]\n\n/// A point\n// in space\nstruct Point;\n";
+		assert_eq!(
+			hoise_crate_attributes(code, "use std::rc::Rc;\n", ""),
+			"#![allow(unused)]\nuse std::rc::Rc;\n\n/// A point\n// in space\nstruct Point;\n"
+		);
+
+		let code = "/// Doubles the number\nfn double(x: u32) -> u32 { x * 2 }\n";
+		assert_eq!(
+			hoise_crate_attributes(code, "use std::rc::Rc;\n", ""),
+			format!("use std::rc::Rc;\n{code}")
+		);
+	}
+
+	#[test]
+	fn eval_lifts_feature_attributes_out_of_main() {
+		let wrapped = maybe_wrap(
+			"#![feature(never_type)]\nlet x: u8 = 1;\nx + 1",
+			ResultHandling::Print,
+		);
+		assert!(
+			wrapped.starts_with("#![feature(never_type)]\n"),
+			"{wrapped}"
+		);
+		assert_eq!(wrapped.matches("#![feature(never_type)]").count(), 1);
+		let main_pos = wrapped.find("fn main()").unwrap();
+		assert!(wrapped[main_pos..].contains("x + 1"));
+		assert!(syn::parse_file(&wrapped).is_ok(), "{wrapped}");
+	}
+
+	#[test]
+	fn eval_keeps_derives_on_items_inside_the_body() {
+		let wrapped = maybe_wrap(
+			"#[derive(Debug)]\nstruct Point(u8);\nPoint(1)",
+			ResultHandling::Print,
+		);
+		let main_pos = wrapped.find("fn main()").unwrap();
+		assert!(
+			wrapped[main_pos..].contains("#[derive(Debug)]\nstruct Point(u8);"),
+			"{wrapped}"
+		);
+		assert!(syn::parse_file(&wrapped).is_ok(), "{wrapped}");
+	}
+
+	#[test]
+	fn line_doc_comments_are_stripped() {
+		let code = "//! Crate docs\n/// Item docs\nfn main() {\n\tlet x = 1; /// trailing\n\t// normal comment\n\t//// not a doc comment\n}\n";
+		assert_eq!(
+			strip_doc_comments(code),
+			"fn main() {\n\tlet x = 1;\n\t// normal comment\n\t//// not a doc comment\n}\n"
+		);
+	}
+
+	#[test]
+	fn block_doc_comments_are_stripped() {
+		let code = "/*! Crate\n * docs */\n/** Item docs */\nfn main() {\n\tlet x = /** inline */ 1;\n\t/* normal */ /**/ /*** not docs */\n}\n";
+		assert_eq!(
+			strip_doc_comments(code),
+			"fn main() {\n\tlet x =  1;\n\t/* normal */ /**/ /*** not docs */\n}\n"
+		);
+	}
+
+	#[test]
+	fn doc_comment_markers_in_literals_are_kept() {
+		let code = "fn main() {\n\tlet quote = '\\'';\n\tlet s = \"/// not docs\";\n\tlet r = r#\"/** nor this */\"#;\n\tlet slash = '/';\n}\n";
+		assert_eq!(strip_doc_comments(code), code);
+	}
+
+	fn killed(stderr: &str, exit_detail: &str) -> api::PlayResult {
+		api::PlayResult {
+			success: false,
+			stdout: String::new(),
+			stderr: stderr.to_owned(),
+			exit_detail: exit_detail.to_owned(),
+		}
+	}
+
+	const WRAPPER_KILLED: &str = "/playground/tools/entrypoint.sh: line 11:     7 Killed                  timeout --signal=KILL ${timeout} \"$@\"";
+
+	#[test]
+	fn timeouts_are_told_apart_from_running_out_of_memory() {
+		assert_eq!(
+			kill_reason(&killed(WRAPPER_KILLED, "exit status: 137")),
+			Some(KillReason::Timeout)
+		);
+		assert_eq!(
+			kill_reason(&killed(WRAPPER_KILLED, "")),
+			Some(KillReason::Timeout)
+		);
+		assert_eq!(
+			kill_reason(&killed(
+				"memory allocation of 1099511627776 bytes failed\n",
+				"signal: 6 (SIGABRT)"
+			)),
+			Some(KillReason::OutOfMemory)
+		);
+		assert_eq!(
+			kill_reason(&killed(
+				"Memory cgroup out of memory: Killed process 7 (playground)",
+				"signal: 9 (SIGKILL)"
+			)),
+			Some(KillReason::OutOfMemory)
+		);
+	}
+
+	#[test]
+	fn kills_without_evidence_are_not_blamed_on_memory() {
+		assert_eq!(
+			kill_reason(&killed("", "signal: 9 (SIGKILL)")),
+			Some(KillReason::Killed)
+		);
+		assert_eq!(
+			kill_reason(&killed(WRAPPER_KILLED, "signal: 9 (SIGKILL)")),
+			Some(KillReason::Killed)
+		);
+		assert_eq!(KillReason::Killed.note(), "Your program was killed.");
+	}
+
+	#[test]
+	fn other_failures_are_not_kills() {
+		assert_eq!(
+			kill_reason(&killed("thread 'main' panicked", "exit status: 101")),
+			None
+		);
+		assert_eq!(
+			kill_reason(&killed("Killed the dragon", "exit status: 0")),
+			None
+		);
+	}
+
+	#[test]
+	fn replayed_flags_parse_back_to_the_same_flags() {
+		for args in [
+			"",
+			"channel=nightly mode=release edition=2018",
+			"warn=true tail=5 wrap=80 maxwarn=3 forbid=unsafe_code,missing_docs stripdocs=true safe=true",
+			"editions=2015,2021 seed=42 embed=true qr=true collapse=true cargotoml=true",
+		] {
+			let (parsed, errors) = flags(args);
+			assert_eq!(errors, "", "flags {args:?}");
+			let replay_args = parsed.replay_args();
+			let (replayed, errors) = flags(&replay_args);
+			assert_eq!(errors, "", "replay {replay_args:?}");
+			assert_eq!(replayed.replay_args(), replay_args);
+			for arg in args.split_whitespace() {
+				assert!(
+					replay_args.split_whitespace().any(|replayed| replayed == arg),
+					"{arg} is missing from {replay_args:?}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn mentions_are_broken_up() {
+		assert_eq!(
+			neutralize_mentions("@everyone @here <@&123> <@456> user@example.com"),
+			"@\u{200b}everyone @\u{200b}here <@\u{200b}&123> <@456> user@example.com"
+		);
+	}
+
+	#[test]
+	fn only_safe_replies_are_neutralized() {
+		let (safe, _) = flags("safe=true");
+		let (unsafe_, _) = flags("");
+		let errors = "unknown flag `@everyone`\n";
+		assert_eq!(
+			neutralize_mentions_if_safe(&safe, errors),
+			"unknown flag `@\u{200b}everyone`\n"
+		);
+		assert_eq!(neutralize_mentions_if_safe(&unsafe_, errors), errors);
+	}
+
+	#[test]
+	fn only_the_first_diagnostics_of_a_kind_are_kept() {
+		let stderr = "   Compiling playground\nerror[E0425]: a\n --> a\nwarning: b\n --> b\nerror: c\n --> c\nerror: d\n";
+		assert_eq!(
+			limit_diagnostics(stderr, "error", 1),
+			(
+				"   Compiling playground\nerror[E0425]: a\n --> a\nwarning: b\n --> b\n".to_owned(),
+				2
+			)
+		);
+		assert_eq!(
+			limit_diagnostics(stderr, "error", 3),
+			(stderr.to_owned(), 0)
+		);
+	}
+
+	#[test]
+	fn tail_keeps_the_last_lines() {
+		assert_eq!(
+			tail_lines("a\nb\nc\n", 2),
+			"(1 earlier lines omitted)\nb\nc\n"
+		);
+		assert_eq!(tail_lines("a\nb\n", 5), "a\nb\n");
+	}
+
+	#[test]
+	fn tail_keeps_fewer_lines_if_they_would_not_fit() {
+		let text = format!("{}\n", "x".repeat(100)).repeat(30);
+		let tail = tail_lines(&text, 30);
+		assert!(tail.starts_with("(16 earlier lines omitted)\n"));
+		assert!(tail.len() < 1600);
+
+		let text = "x\n".repeat(100);
+		assert!(tail_lines(&text, 100).starts_with("(56 earlier lines omitted)\n"));
+	}
+
+	#[test]
+	fn repeated_lines_are_collapsed() {
+		assert_eq!(
+			dedupe_lines("a\na\na\nb\na\nc\nc\n"),
+			"a (repeated 3 times)\nb\na\nc (repeated 2 times)\n"
+		);
+		assert_eq!(dedupe_lines(""), "");
+	}
+
+	#[test]
+	fn long_lines_are_wrapped_by_characters() {
+		assert_eq!(wrap_lines("abcdefg\nhi\n", 3), "abc\ndef\ng\nhi\n");
+		assert_eq!(wrap_lines("ééé", 2), "éé\né\n");
+		assert_eq!(wrap_lines("a\n\nb", 3), "a\n\nb\n");
+	}
+
+	#[test]
+	fn inline_suggestions_are_applied() {
+		let stderr = "warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+  |
+  = note: `#[warn(unused_variables)]` on by default
+";
+		assert_eq!(
+			apply_inline_suggestions("fn main() {\n    let x = 5;\n}\n", stderr),
+			("fn main() {\n    let _x = 5;\n}\n".to_owned(), 1)
+		);
+		// Tabs are shown as four spaces
+		assert_eq!(
+			apply_inline_suggestions("fn main() {\n\tlet x = 5;\n}", stderr),
+			("fn main() {\n\tlet _x = 5;\n}".to_owned(), 1)
+		);
+	}
+
+	#[test]
+	fn suggestions_for_other_code_are_skipped() {
+		let stderr = "  |
+2 |     let y = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_y`
+";
+		let code = "fn main() {\n    let x = 5;\n}\n";
+		assert_eq!(apply_inline_suggestions(code, stderr), (code.to_owned(), 0));
+	}
+
+	#[test]
+	fn blocking_calls_in_async_code_are_found() {
+		let code = r#"
+			async fn load() -> String {
+				std::thread::sleep(Duration::from_secs(1));
+				let text = fs::read_to_string("a").unwrap();
+				tokio::fs::read_to_string("b").await.unwrap();
+				tokio::task::spawn_blocking(move || std::fs::write("c", "")).await;
+				// std::thread::sleep in a comment
+				let s = "fs::read in a string";
+				text
+			}
+			fn main() {
+				std::thread::sleep(Duration::from_secs(1));
+				let _ = async { File::open("d") };
+			}
+		"#;
+		assert_eq!(
+			find_blocking_in_async(code),
+			["std::thread::sleep", "fs::read_to_string", "File::open"]
+		);
+	}
+
+	#[test]
+	fn code_without_async_or_that_does_not_lex_has_no_blocking_calls() {
+		assert!(find_blocking_in_async("fn main() { std::thread::sleep(d); }").is_empty());
+		assert!(find_blocking_in_async("async fn f() { \"unterminated }").is_empty());
+	}
+
+	#[test]
+	fn manifest_depends_on_the_crates_the_code_uses() {
+		let available = [
+			("rand", "0.8.5", "rand"),
+			("serde-json", "1.0.0", "serde_json"),
+			("regex", "1.10.0", "regex"),
+		]
+		.map(|(name, version, id)| api::CrateInfo {
+			name: name.to_owned(),
+			version: version.to_owned(),
+			id: id.to_owned(),
+		});
+		let code = "use serde_json::json;\nfn main() { let x: u8 = rand::random(); let s = \"regex::Regex\"; }";
+		assert_eq!(
+			cargo_manifest(code, api::Edition::E2021, &available),
+			"[package]\nname = \"playground\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+			[dependencies]\nrand = \"0.8.5\"\nserde-json = \"1.0.0\"\n"
+		);
+	}
+
+	#[test]
+	fn only_compiler_warnings_are_limited() {
+		let stderr = "   Compiling playground v0.0.1 (/playground)
+warning: unused variable: `a`
+ --> src/main.rs:2:9
+warning: unused variable: `b`
+ --> src/main.rs:3:9
+warning: `playground` (bin \"playground\") generated 2 warnings
+    Finished dev [unoptimized + debuginfo] target(s) in 0.5s
+     Running `target/debug/playground`
+warning: printed by the program
+";
+		let (limited, omitted) = limit_compiler_warnings(stderr, 1);
+		assert_eq!(omitted, 1);
+		assert!(limited.contains("`a`"));
+		assert!(!limited.contains("`b`"));
+		assert!(limited.contains("generated 2 warnings"));
+		assert!(limited.ends_with("warning: printed by the program\n"));
+	}
+
+	#[test]
+	fn every_table_flag_is_parsed_and_replayed() {
+		let mut names = std::collections::HashSet::new();
+		for flag in api::BOOL_FLAGS {
+			assert!(names.insert(flag.name), "{} is listed twice", flag.name);
+			let (parsed, errors) = flags(&format!("{}=true", flag.name));
+			assert_eq!(errors, "", "flag {}", flag.name);
+			assert!((flag.get)(&parsed), "{} wasn't set", flag.name);
+			if flag.name != "replay" {
+				let arg = format!("{}=true", flag.name);
+				let replay_args = parsed.replay_args();
+				assert!(replay_args
+					.split_whitespace()
+					.any(|replayed| replayed == arg));
+			}
+		}
+		for flag in api::NUMBER_FLAGS {
+			assert!(names.insert(flag.name), "{} is listed twice", flag.name);
+			let (parsed, errors) = flags(&format!("{}=7", flag.name));
+			assert_eq!(errors, "", "flag {}", flag.name);
+			let value = (flag.get)(&parsed).map(std::num::NonZeroUsize::get);
+			assert_eq!(value, Some(7), "flag {}", flag.name);
+			let arg = format!("{}=7", flag.name);
+			let replay_args = parsed.replay_args();
+			assert!(replay_args
+				.split_whitespace()
+				.any(|replayed| replayed == arg));
+		}
+	}
+
+	#[test]
+	fn qr_code_is_attached_with_the_link() {
+		let url = "https://play.rust-lang.org/?gist=abc";
+		let (footer, attachment) = qr_code_attachment("took 1.00s\n", url).unwrap();
+		assert_eq!(footer, format!("took 1.00s\nplayground: <{url}>"));
+		assert_eq!(attachment.filename, "playground.png");
+		assert!(attachment.data.starts_with(b"\x89PNG"));
+
+		let reply = poise::CreateReply::default().attachment(attachment);
+		assert_eq!(reply.attachments.len(), 1);
+		let (footer, _) = qr_code_attachment("", url).unwrap();
+		assert_eq!(footer, format!("playground: <{url}>"));
+	}
+
+	#[test]
+	fn flags_given_to_the_command_win_over_the_directive() {
+		let mut flags = poise::KeyValueArgs::default();
+		flags.0.insert("mode".to_owned(), "debug".to_owned());
+		let code = "//! play: mode=release tail=5\nfn main() {}";
+		let (code, errors) = split_flag_directive(code, &mut flags);
+		assert_eq!(code, "fn main() {}");
+		assert_eq!(errors, "");
+		assert_eq!(flags.get("mode"), Some("debug"));
+		assert_eq!(flags.get("tail"), Some("5"));
+	}
+
+	#[test]
+	fn malformed_directive_tokens_are_reported() {
+		let mut flags = poise::KeyValueArgs::default();
+		let code = "//! play: tail= =3 wrap\nfn main() {}";
+		let (code, errors) = split_flag_directive(code, &mut flags);
+		assert_eq!(code, "fn main() {}");
+		assert_eq!(errors.lines().count(), 3);
+		assert!(errors.contains("`wrap`"));
+		assert!(flags.0.is_empty());
+	}
+
+	#[test]
+	fn code_without_a_directive_is_untouched() {
+		let mut flags = poise::KeyValueArgs::default();
+		let code = "// play: tail=5\nfn main() {}";
+		assert_eq!(
+			split_flag_directive(code, &mut flags),
+			(code, String::new())
+		);
+		assert!(flags.0.is_empty());
+	}
+
+	#[test]
+	fn unsafe_keywords_are_counted() {
+		let code = "
+			unsafe fn f() {}
+			fn main() {
+				unsafe { f() }
+			}
+		";
+		assert_eq!(count_unsafe(code), Some(2));
+		assert_eq!(count_unsafe("fn main() {}"), Some(0));
+	}
+
+	#[test]
+	fn unsafe_in_strings_and_comments_is_not_counted() {
+		let code = r#"
+			// unsafe { this is not code }
+			fn main() {
+				println!("unsafe");
+			}
+		"#;
+		assert_eq!(count_unsafe(code), Some(0));
+		assert_eq!(count_unsafe("fn main() { unsafe {"), None);
+	}
+
+	#[test]
+	fn network_apis_are_detected() {
+		assert!(uses_network(
+			"use std::net::TcpStream; fn main() { TcpStream::connect(\"a:1\"); }"
+		));
+		assert!(uses_network(
+			"fn main() { let _ = std :: net :: Ipv4Addr::LOCALHOST; }"
+		));
+		assert!(uses_network(
+			"fn main() { reqwest::blocking::get(\"https://a\"); }"
+		));
+	}
+
+	#[test]
+	fn network_mentions_outside_code_are_ignored() {
+		assert!(!uses_network(
+			"// reqwest::get\nfn main() { println!(\"std::net::TcpStream\"); }"
+		));
+		assert!(!uses_network("mod net {} fn main() { let net = 1; }"));
+		assert!(!uses_network("fn main() { TcpStream("));
+	}
+
+	#[test]
+	fn warnings_are_grouped_by_lint() {
+		let stderr = "\
+warning: unused variable: `a`
+ --> src/main.rs:2:9
+  |
+  = note: `#[warn(unused_variables)]` on by default
+
+warning: function `f` is never used
+ --> src/main.rs:6:4
+  |
+  = note: `#[warn(dead_code)]` on by default
+
+warning: unused variable: `b`
+ --> src/main.rs:3:9
+
+warning: `playground` (bin \"playground\") generated 3 warnings
+warning: 3 warnings emitted
+";
+		assert_eq!(
+			group_warnings_by_lint(stderr).as_deref(),
+			Some("warnings: unused_variables (2), dead_code (1)\n")
+		);
+	}
+
+	#[test]
+	fn output_without_warnings_has_no_lint_summary() {
+		assert_eq!(group_warnings_by_lint(""), None);
+		assert_eq!(
+			group_warnings_by_lint("error[E0425]: cannot find value `x` in this scope"),
+			None
+		);
+	}
+
+	#[test]
+	fn issue_body_has_code_meta_and_output_sections() {
+		let version = api::VersionResponse {
+			version: "1.80.0-nightly".to_owned(),
+			hash: "0123456789abcdef".to_owned(),
+			date: "2024-06-01".to_owned(),
+		};
+		let body = issue_body(
+			"fn main() {}\n",
+			&api::CommandFlags::default(),
+			Some(&version),
+			"thread 'main' panicked\n",
+			Some("https://play.rust-lang.org/?gist=abc"),
+		);
+		assert_eq!(
+			body,
+			"### Code\n\n```rust\nfn main() {}\n```\n\n\
+			### Meta\n\n\
+			`rustc 1.80.0-nightly (012345678 2024-06-01)`\n\
+			Ran on the Rust playground with channel=nightly mode=debug edition=2021\n\n\
+			### Output\n\n```\nthread 'main' panicked\n```\n\
+			\n[Playground link](https://play.rust-lang.org/?gist=abc)\n"
+		);
+	}
+
+	#[test]
+	fn issue_body_without_version_or_link() {
+		let body = issue_body("", &api::CommandFlags::default(), None, "", None);
+		assert!(body.contains("### Meta\n\nrustc version unknown\n"));
+		assert!(body.ends_with("### Output\n\n```\n\n```\n"));
+		assert!(!body.contains("Playground link"));
+	}
+
+	fn result(success: bool, stdout: &str, stderr: &str) -> api::PlayResult {
+		api::PlayResult {
+			success,
+			stdout: stdout.to_owned(),
+			stderr: stderr.to_owned(),
+			exit_detail: String::new(),
+		}
+	}
+
+	#[test]
+	fn outcome_matrix_shows_the_first_error_of_each_run() {
+		let rows = [
+			("stable", result(true, "", "")),
+			(
+				"nightly",
+				result(
+					false,
+					"",
+					"warning: unused variable\nerror[E0308]: mismatched types\nerror: aborting",
+				),
+			),
+			("beta", result(false, "", "\nthread 'main' panicked\n")),
+			("2015", result(false, "", "")),
+		];
+		assert_eq!(
+			outcome_matrix(&rows),
+			"stable  ✅\n\
+			nightly ❌ error[E0308]: mismatched types\n\
+			beta    ❌ thread 'main' panicked\n\
+			2015    ❌ \n"
+		);
+	}
+
+	#[test]
+	fn mode_divergence_is_explained() {
+		let overflow = result(false, "", "attempt to add with overflow");
+		let wrapped = result(true, "0\n", "");
+		assert!(describe_mode_divergence(&overflow, &wrapped)
+			.starts_with("debug panicked on an arithmetic overflow"));
+		assert_eq!(
+			describe_mode_divergence(&result(false, "", "error"), &wrapped),
+			"debug failed, but release succeeded"
+		);
+		assert_eq!(
+			describe_mode_divergence(&wrapped, &overflow),
+			"release failed, but debug succeeded"
+		);
+		assert_eq!(
+			describe_mode_divergence(&overflow, &overflow),
+			"both debug and release failed"
+		);
+		assert!(describe_mode_divergence(&wrapped, &result(true, "1\n", ""))
+			.starts_with("debug and release printed different output"));
+		assert_eq!(
+			describe_mode_divergence(&wrapped, &wrapped),
+			"debug and release behaved the same"
+		);
+	}
+
+	#[test]
+	fn line_lengths_are_bucketed() {
+		// Longest line is 9 characters, so five buckets of two
+		let text = "a\nab\nabc\nabcd\nabcd\nabcdefghi";
+		assert_eq!(
+			line_length_histogram(text).as_deref(),
+			Some(
+				"0-1 ██████████ 1\n\
+				2-3 ████████████████████ 2\n\
+				4-5 ████████████████████ 2\n\
+				6-7  0\n\
+				8-9 ██████████ 1\n"
+			)
+		);
+		assert_eq!(line_length_histogram(""), None);
+	}
+
+	#[test]
+	fn clippy_diagnostics_are_found() {
+		assert!(has_clippy_diagnostics(
+			"    Checking playground v0.0.1\nwarning: this loop never actually loops\n"
+		));
+		assert!(has_clippy_diagnostics(
+			"error: could not compile `playground`"
+		));
+		assert!(!has_clippy_diagnostics(
+			"    Checking playground v0.0.1\n    Finished dev [unoptimized] target(s)\n"
+		));
+	}
+
+	#[test]
+	fn style_summary_covers_clippy_and_rustfmt() {
+		assert_eq!(
+			style_summary(true, Some(true)),
+			"✅ clippy clean, ✅ rustfmt-formatted"
+		);
+		assert_eq!(
+			style_summary(false, Some(false)),
+			"❌ not clippy clean (run ?clippy), ❌ not rustfmt-formatted (run ?fmt)"
+		);
+		assert_eq!(
+			style_summary(true, None),
+			"✅ clippy clean, ❌ rustfmt couldn't parse the code"
+		);
+	}
+
+	#[test]
+	fn size_reduction_is_described_in_percent() {
+		assert_eq!(
+			describe_size_reduction(200, 50),
+			"release is 75% smaller assembly than debug (200 → 50 instructions)"
+		);
+		assert_eq!(
+			describe_size_reduction(100, 150),
+			"release is 50% larger assembly than debug (100 → 150 instructions)"
+		);
+		assert_eq!(
+			describe_size_reduction(0, 0),
+			"no assembly to compare (0 → 0 instructions)"
+		);
+	}
+
+	#[test]
+	fn sections_are_labeled_and_joined() {
+		assert_eq!(
+			labeled_sections([("debug", "1\n2\n\n"), ("release", ""), ("2015", "3")]),
+			"--- debug ---\n1\n2\n--- release ---\n\n--- 2015 ---\n3\n"
+		);
+		assert_eq!(labeled_sections([]), "");
+	}
+
+	#[test]
+	fn every_reply_kind_shapes_the_output_the_same_way() {
+		let (shaping, errors) = flags("tail=2 wrap=3 showtoolchain=true");
+		assert_eq!(errors, "");
+		let output = merged_output(&result(true, "1\n2\nabcdef\n", ""), &shaping);
+		assert_eq!(
+			output,
+			shaping.toolchain_comment() + "(2 earlier lines omitted)\nabc\ndef\n"
+		);
+		// Embed fields get the same treatment, minus the toolchain shown in the embed footer
+		assert_eq!(
+			shape_output("1\n2\nabcdef\n", &shaping),
+			"(2 earlier lines omitted)\nabc\ndef\n"
+		);
+
+		let (plain, _) = flags("");
+		assert_eq!(shape_output("1\n2\n", &plain), "1\n2\n");
+	}
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Error, Result};
+use poise::serenity_prelude as serenity;
+use shuttle_runtime::SecretStore;
+
+use crate::commands::playground::Channel;
+
+/// Operator-tunable settings. Every field is optional in the secret store and defaults to the
+/// behavior of the bot running on the Rust Community Discord server.
+#[derive(Debug, Clone)]
+pub struct Config {
+	/// Prefix for prefix commands (`PREFIX`, default `?`)
+	pub prefix: String,
+	/// Base URL of the Rust playground instance to run code on, without trailing slash
+	/// (`PLAYGROUND_URL`, default `https://play.rust-lang.org`)
+	pub playground_url: String,
+	/// Referer sent along with gist creation requests, so the playground knows where they come
+	/// from (`PLAYGROUND_REFERER`, default `https://discord.gg/rust-lang-community`)
+	pub playground_referer: String,
+	/// Release channel used by playground commands when none is given
+	/// (`PLAYGROUND_DEFAULT_CHANNEL`, default `nightly`)
+	pub default_channel: Channel,
+	/// If set, output that is too large is cut off without creating a playground gist
+	/// (`PLAYGROUND_NO_GIST`, default `false`)
+	pub no_gist: bool,
+	/// Channels in which playground commands may be used. Empty means everywhere
+	/// (`PLAYGROUND_ALLOWED_CHANNELS`, comma separated channel IDs, default empty)
+	pub playground_allowed_channels: Vec<serenity::ChannelId>,
+	/// URL that moderators can have playground results posted to as JSON with `webhook=true`
+	/// (`PLAYGROUND_WEBHOOK_URL`, default none, which disables the flag)
+	pub playground_webhook_url: Option<String>,
+	/// Directory that code run with `permalink=true` is stored in, to be retrieved with `?get`
+	/// (`PLAYGROUND_PERMALINK_DIR`, default none, which disables the flag)
+	pub playground_permalink_dir: Option<PathBuf>,
+	/// How long after a playground command ran an edit of the invoking message may rerun it
+	/// (`PLAYGROUND_EDIT_COOLDOWN_SECS`, default `5`)
+	pub playground_edit_cooldown: Duration,
+	/// How long the result of running some code is reused when the same code is run with the
+	/// same settings again (`PLAYGROUND_RESULT_CACHE_SECS`, default `0`, which disables caching)
+	pub playground_result_cache_ttl: Duration,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			prefix: "?".into(),
+			playground_url: "https://play.rust-lang.org".into(),
+			playground_referer: "https://discord.gg/rust-lang-community".into(),
+			default_channel: Channel::Nightly,
+			no_gist: false,
+			playground_allowed_channels: Vec::new(),
+			playground_webhook_url: None,
+			playground_permalink_dir: None,
+			playground_edit_cooldown: Duration::from_secs(5),
+			playground_result_cache_ttl: Duration::ZERO,
+		}
+	}
+}
+
+impl Config {
+	pub fn new(secret_store: &SecretStore) -> Result<Self> {
+		Self::from_lookup(|key| secret_store.get(key))
+	}
+
+	/// Builds the config from an arbitrary key-value source, falling back to the defaults for
+	/// missing keys
+	pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+		let mut config = Self::default();
+
+		if let Some(prefix) = lookup("PREFIX") {
+			config.prefix = prefix;
+		}
+		if let Some(playground_url) = lookup("PLAYGROUND_URL") {
+			config.playground_url = playground_url.trim_end_matches('/').to_owned();
+		}
+		if let Some(playground_referer) = lookup("PLAYGROUND_REFERER") {
+			config.playground_referer = playground_referer;
+		}
+		if let Some(default_channel) = lookup("PLAYGROUND_DEFAULT_CHANNEL") {
+			config.default_channel = default_channel.parse()?;
+		}
+		if let Some(no_gist) = lookup("PLAYGROUND_NO_GIST") {
+			config.no_gist = no_gist
+				.parse()
+				.map_err(|e| anyhow!("invalid 'PLAYGROUND_NO_GIST': {e}"))?;
+		}
+		if let Some(allowed_channels) = lookup("PLAYGROUND_ALLOWED_CHANNELS") {
+			config.playground_allowed_channels = allowed_channels
+				.split(',')
+				.map(str::trim)
+				.filter(|id| !id.is_empty())
+				.map(|id| Ok::<_, Error>(id.parse::<u64>()?.into()))
+				.collect::<Result<_>>()
+				.map_err(|e| anyhow!("invalid 'PLAYGROUND_ALLOWED_CHANNELS': {e}"))?;
+		}
+		if let Some(webhook_url) = lookup("PLAYGROUND_WEBHOOK_URL") {
+			config.playground_webhook_url = Some(webhook_url).filter(|url| !url.is_empty());
+		}
+		if let Some(permalink_dir) = lookup("PLAYGROUND_PERMALINK_DIR") {
+			config.playground_permalink_dir =
+				Some(PathBuf::from(permalink_dir)).filter(|dir| !dir.as_os_str().is_empty());
+		}
+		if let Some(edit_cooldown) = lookup("PLAYGROUND_EDIT_COOLDOWN_SECS") {
+			config.playground_edit_cooldown = Duration::from_secs(
+				edit_cooldown
+					.parse()
+					.map_err(|e| anyhow!("invalid 'PLAYGROUND_EDIT_COOLDOWN_SECS': {e}"))?,
+			);
+		}
+
+		if let Some(cache_ttl) = lookup("PLAYGROUND_RESULT_CACHE_SECS") {
+			config.playground_result_cache_ttl = Duration::from_secs(
+				cache_ttl
+					.parse()
+					.map_err(|e| anyhow!("invalid 'PLAYGROUND_RESULT_CACHE_SECS': {e}"))?,
+			);
+		}
+
+		Ok(config)
+	}
+
+	/// URL of the given playground endpoint, e.g. `execute` or `meta/gist/`
+	#[must_use]
+	pub fn playground_endpoint(&self, path: &str) -> String {
+		format!("{}/{path}", self.playground_url)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn config(vars: &[(&str, &str)]) -> Result<Config> {
+		Config::from_lookup(|key| {
+			vars.iter()
+				.find(|(name, _)| *name == key)
+				.map(|(_, value)| (*value).to_owned())
+		})
+	}
+
+	#[test]
+	fn missing_keys_use_the_defaults() {
+		let config = config(&[]).unwrap();
+		assert_eq!(config.prefix, "?");
+		assert_eq!(config.playground_url, "https://play.rust-lang.org");
+		assert!(matches!(config.default_channel, Channel::Nightly));
+		assert!(!config.no_gist);
+		assert!(config.playground_allowed_channels.is_empty());
+		assert_eq!(config.playground_webhook_url, None);
+		assert_eq!(config.playground_permalink_dir, None);
+		assert_eq!(config.playground_edit_cooldown, Duration::from_secs(5));
+		assert_eq!(config.playground_result_cache_ttl, Duration::ZERO);
+	}
+
+	#[test]
+	fn keys_override_the_defaults() {
+		let config = config(&[
+			("PREFIX", "!"),
+			("PLAYGROUND_URL", "https://play.example.com//"),
+			("PLAYGROUND_DEFAULT_CHANNEL", "stable"),
+			("PLAYGROUND_ALLOWED_CHANNELS", "123, 456,"),
+			("PLAYGROUND_WEBHOOK_URL", ""),
+			("PLAYGROUND_RESULT_CACHE_SECS", "90"),
+		])
+		.unwrap();
+		assert_eq!(config.prefix, "!");
+		assert_eq!(config.playground_url, "https://play.example.com");
+		assert_eq!(
+			config.playground_endpoint("execute"),
+			"https://play.example.com/execute"
+		);
+		assert!(matches!(config.default_channel, Channel::Stable));
+		assert_eq!(
+			config.playground_allowed_channels,
+			[serenity::ChannelId::new(123), serenity::ChannelId::new(456)]
+		);
+		assert_eq!(config.playground_webhook_url, None);
+		assert_eq!(config.playground_result_cache_ttl, Duration::from_secs(90));
+	}
+
+	#[test]
+	fn invalid_values_are_rejected() {
+		for (key, value) in [
+			("PLAYGROUND_EDIT_COOLDOWN_SECS", "5s"),
+			("PLAYGROUND_RESULT_CACHE_SECS", "-1"),
+			("PLAYGROUND_ALLOWED_CHANNELS", "123,general"),
+			("PLAYGROUND_NO_GIST", "yes"),
+		] {
+			let error = config(&[(key, value)]).unwrap_err();
+			assert!(error.to_string().contains(key), "{error}");
+		}
+		assert!(config(&[("PLAYGROUND_DEFAULT_CHANNEL", "unstable")]).is_err());
+	}
+}
@@ -1,5 +1,8 @@
+use std::fmt::Write as _;
+
 use anyhow::Error;
 use poise::serenity_prelude as serenity;
+use poise::CodeBlockError;
 use tracing::warn;
 
 use crate::types::{Context, Data};
@@ -116,16 +119,22 @@ pub async fn trim_text(
 	const MAX_OUTPUT_LINES: usize = 45;
 	const MAX_OUTPUT_LENGTH: usize = 2000;
 
-	let needs_truncating = text_body.len() + text_end.len() > MAX_OUTPUT_LENGTH
+	// Discord's limit is in characters. Counting bytes would make non-ASCII output that fits look
+	// too long, and the truncation message may create a gist, so this needs to be exact
+	let needs_truncating = text_body.chars().count() + text_end.chars().count() > MAX_OUTPUT_LENGTH
 		|| text_body.lines().count() > MAX_OUTPUT_LINES;
 
+	// The truncation message is only awaited here, so short output never creates a gist
 	if needs_truncating {
 		let truncation_msg = truncation_msg_future.await;
 
 		// truncate for length
 		let text_body: String = text_body
 			.chars()
-			.take(MAX_OUTPUT_LENGTH - truncation_msg.len() - text_end.len())
+			.take(
+				MAX_OUTPUT_LENGTH
+					.saturating_sub(truncation_msg.chars().count() + text_end.chars().count()),
+			)
 			.collect();
 
 		// truncate for lines
@@ -151,3 +160,315 @@ pub async fn reply_potentially_long_text(
 		.await?;
 	Ok(())
 }
+
+/// A prefix command parameter for Discord code blocks.
+///
+/// Works like [`poise::CodeBlock`], but also understands fences of more than three backticks,
+/// which Discord users need for code that itself contains triple backticks.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+	/// The text inside the code block
+	pub code: String,
+	/// In multiline code blocks, the language code, if present
+	pub language: Option<String>,
+}
+
+/// Reads a code block from the front of the string and returns the remaining string.
+///
+/// Single or double backticks make an inline code block. Three or more backticks make a
+/// multiline code block, which ends at the next run of the same number of backticks.
+///
+/// Neither does the fence need to start on a new line nor does the code need to span multiple
+/// lines, so the command, its flags and a short code block can all be written on one line. Like
+/// in Discord, a language is only recognized if the opening fence is directly followed by it and
+/// a newline.
+pub fn extract_code(args: &str) -> Result<(&str, CodeBlock), CodeBlockError> {
+	let args = args.trim_start();
+	let fence_len = args.len() - args.trim_start_matches('`').len();
+	let (fence, body) = args.split_at(fence_len);
+	if fence.is_empty() {
+		return Err(CodeBlockError::default());
+	}
+
+	let code_end = body.find(fence).ok_or_else(CodeBlockError::default)?;
+	let rest = &body[(code_end + fence_len)..];
+	let mut code = &body[..code_end];
+
+	let mut language = None;
+	if fence_len >= 3 {
+		// If a string is preceded directly by the backticks and succeeded directly by a newline,
+		// it's interpreted as the code block language
+		if let Some(first_newline) = code.find('\n') {
+			// Language idents may only consist of [A-Za-z0-9+-._]. A fence directly followed by a
+			// newline has no language at all
+			let is_valid = code[..first_newline]
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+			if is_valid && first_newline > 0 {
+				language = Some(code[..first_newline].to_owned());
+				code = &code[(first_newline + 1)..];
+			}
+		}
+
+		// Discord strips empty lines from start and end, but only if they're really empty
+		code = code.trim_start_matches('\n').trim_end_matches('\n');
+	}
+
+	// Discord likes to insert hair spaces at the end of code blocks sometimes for no reason
+	let code = code.trim_end_matches('\u{200a}');
+
+	// Empty code blocks like `` are not rendered as code blocks by Discord
+	if code.is_empty() {
+		return Err(CodeBlockError::default());
+	}
+
+	Ok((
+		rest,
+		CodeBlock {
+			code: code.to_owned(),
+			language,
+		},
+	))
+}
+
+#[poise::async_trait]
+impl<'a> poise::PopArgument<'a> for CodeBlock {
+	async fn pop_from(
+		args: &'a str,
+		attachment_index: usize,
+		_: &serenity::Context,
+		_: &serenity::Message,
+	) -> Result<(&'a str, usize, Self), (Box<dyn std::error::Error + Send + Sync>, Option<String>)>
+	{
+		let (rest, code_block) = extract_code(args).map_err(|e| (e.into(), None))?;
+		Ok((rest, attachment_index, code_block))
+	}
+}
+
+/// If the arguments of a command which expects a code block look like Rust code that was sent
+/// without one, returns a reply suggesting the same code wrapped in a code block, ready to copy
+#[must_use]
+pub fn suggest_code_block(args: &str) -> Option<String> {
+	const RUST_MARKERS: &[&str] = &[
+		"fn ", "let ", "struct ", "enum ", "impl ", "use ", "println!",
+	];
+
+	// Skip over `key=value` flags in front of the code
+	let mut code = args.trim();
+	while let Some((token, rest)) = code.split_once(char::is_whitespace) {
+		let is_flag = token.split_once('=').is_some_and(|(key, value)| {
+			!key.is_empty()
+				&& !value.is_empty()
+				&& key
+					.chars()
+					.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+		});
+		if !is_flag {
+			break;
+		}
+		code = rest.trim_start();
+	}
+
+	let looks_like_rust = !code.contains('`')
+		&& (code.contains(';') || code.contains('}'))
+		&& RUST_MARKERS.iter().any(|marker| code.contains(marker));
+	looks_like_rust.then(|| {
+		format!(
+			"Missing code block. Send your code like this instead:\n\
+			```ansi\n`\x1b[0m`\x1b[0m`rust\n{code}\n`\x1b[0m`\x1b[0m`\n```"
+		)
+	})
+}
+
+/// Reply to a command that expected a code block but didn't get one. Suggests the fenced code if
+/// the raw arguments look like Rust code, and otherwise explains how to send code
+#[must_use]
+pub fn missing_code_block_reply(ctx: Context<'_>) -> String {
+	const FAILED_CODEBLOCK: &str = "\
+Missing code block. Please use the following markdown:
+`` `code here` ``
+or
+```ansi
+`\x1b[0m`\x1b[0m`rust
+code here
+`\x1b[0m`\x1b[0m`
+```";
+
+	let args = match ctx {
+		Context::Prefix(prefix_ctx) => prefix_ctx.args,
+		Context::Application(_) => "",
+	};
+	suggest_code_block(args).unwrap_or_else(|| FAILED_CODEBLOCK.to_owned())
+}
+
+/// Line by line diff of two texts, with removed lines prefixed by `- `, added ones by `+ ` and
+/// unchanged ones by two spaces. Returns `None` if the texts have the same lines.
+#[must_use]
+pub fn diff_lines(old: &str, new: &str) -> Option<String> {
+	// The diff is quadratic in the number of lines, and wouldn't fit into a message anyways
+	const MAX_DIFF_LINES: usize = 500;
+
+	let old = old.lines().take(MAX_DIFF_LINES).collect::<Vec<_>>();
+	let new = new.lines().take(MAX_DIFF_LINES).collect::<Vec<_>>();
+	if old == new {
+		return None;
+	}
+
+	// common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+	let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+	for i in (0..old.len()).rev() {
+		for j in (0..new.len()).rev() {
+			common[i][j] = if old[i] == new[j] {
+				common[i + 1][j + 1] + 1
+			} else {
+				common[i + 1][j].max(common[i][j + 1])
+			};
+		}
+	}
+
+	let mut output = String::new();
+	let (mut i, mut j) = (0, 0);
+	while i < old.len() || j < new.len() {
+		if i < old.len() && j < new.len() && old[i] == new[j] {
+			let _ = writeln!(output, "  {}", old[i]);
+			i += 1;
+			j += 1;
+		} else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+			let _ = writeln!(output, "- {}", old[i]);
+			i += 1;
+		} else {
+			let _ = writeln!(output, "+ {}", new[j]);
+			j += 1;
+		}
+	}
+	Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicBool, Ordering};
+
+	use super::*;
+
+	/// Stands in for the truncation message, which posts a gist
+	const GIST_MESSAGE: &str = "\nOutput too large. Playground link: <gist>";
+
+	#[test]
+	fn rust_without_code_block_gets_a_fenced_suggestion() {
+		let suggestion = suggest_code_block("fn main() { println!(\"hi\"); }").unwrap();
+		assert!(suggestion.contains("rust\nfn main() { println!(\"hi\"); }\n"));
+
+		// Flags in front of the code aren't part of it
+		let suggestion = suggest_code_block("mode=release let x = 1;").unwrap();
+		assert!(suggestion.contains("rust\nlet x = 1;\n"));
+		assert!(!suggestion.contains("mode=release"));
+	}
+
+	#[test]
+	fn other_text_gets_no_suggestion() {
+		for args in [
+			"",
+			"mode=release",
+			"how do I print things",
+			"should I use a struct or an enum here",
+			"`fn main() {}`",
+		] {
+			assert_eq!(suggest_code_block(args), None, "{args}");
+		}
+	}
+
+	#[tokio::test]
+	async fn short_output_never_posts_a_gist() {
+		let posted = AtomicBool::new(false);
+		// Multibyte characters count once, so this fits even though it's over 2000 bytes
+		let body = format!("```\n{}", "é".repeat(1500));
+		let text = trim_text(&body, "```", async {
+			posted.store(true, Ordering::Relaxed);
+			GIST_MESSAGE.to_owned()
+		})
+		.await;
+		assert_eq!(text, format!("{body}```"));
+		assert!(!posted.load(Ordering::Relaxed));
+	}
+
+	#[tokio::test]
+	async fn long_output_is_truncated_with_the_gist_message() {
+		let posted = AtomicBool::new(false);
+		let body = format!("```\n{}", "x".repeat(3000));
+		let text = trim_text(&body, "```", async {
+			posted.store(true, Ordering::Relaxed);
+			GIST_MESSAGE.to_owned()
+		})
+		.await;
+		assert!(posted.load(Ordering::Relaxed));
+		assert_eq!(text.chars().count(), 2000);
+		assert!(text.ends_with("```\nOutput too large. Playground link: <gist>"));
+
+		let posted = AtomicBool::new(false);
+		let body = "line\n".repeat(50);
+		let text = trim_text(&body, "```", async {
+			posted.store(true, Ordering::Relaxed);
+			GIST_MESSAGE.to_owned()
+		})
+		.await;
+		assert!(posted.load(Ordering::Relaxed));
+		assert_eq!(text.lines().count(), 46);
+	}
+
+	fn code(args: &str) -> (&str, String) {
+		let (rest, code_block) = extract_code(args).unwrap();
+		(rest, code_block.code)
+	}
+
+	#[test]
+	fn code_blocks_can_be_fenced_with_any_number_of_backticks() {
+		assert_eq!(code("`a`"), ("", "a".to_owned()));
+		assert_eq!(code("```\na\n```"), ("", "a".to_owned()));
+		assert_eq!(
+			code("````\nlet s = \"```\";\n```` rest"),
+			(" rest", "let s = \"```\";".to_owned())
+		);
+		assert_eq!(code("  ```a``` after"), (" after", "a".to_owned()));
+	}
+
+	#[test]
+	fn unclosed_and_empty_code_blocks_are_rejected() {
+		assert!(extract_code("no code block").is_err());
+		assert!(extract_code("```\nfn main() {}").is_err());
+		assert!(extract_code("``").is_err());
+		assert!(extract_code("```\n\n```").is_err());
+	}
+
+	#[test]
+	fn language_is_only_taken_from_a_fence_followed_by_a_newline() {
+		let (_, block) = extract_code("```rust\nfn main() {}\n```").unwrap();
+		assert_eq!(block.language.as_deref(), Some("rust"));
+		assert_eq!(block.code, "fn main() {}");
+
+		let (_, block) = extract_code("```\nrust\n```").unwrap();
+		assert_eq!(block.language, None);
+		assert_eq!(block.code, "rust");
+
+		let (_, block) = extract_code("```fn main() {}```").unwrap();
+		assert_eq!(block.language, None);
+
+		let (_, block) = extract_code("```let x = 1;\n```").unwrap();
+		assert_eq!(block.language, None);
+		assert_eq!(block.code, "let x = 1;");
+	}
+
+	#[test]
+	fn identical_lines_have_no_diff() {
+		assert_eq!(diff_lines("a\nb\n", "a\nb"), None);
+	}
+
+	#[test]
+	fn changed_lines_are_marked() {
+		assert_eq!(
+			diff_lines("a\nb\nc\n", "a\nB\nc\nd\n").unwrap(),
+			"  a\n- b\n+ B\n  c\n+ d\n"
+		);
+		assert_eq!(diff_lines("", "a").unwrap(), "+ a\n");
+		assert_eq!(diff_lines("a", "").unwrap(), "- a\n");
+	}
+}
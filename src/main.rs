@@ -13,6 +13,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::commands::modmail::{create_modmail_thread, load_or_create_modmail_message};
+use crate::config::Config;
 use crate::types::Data;
 use anyhow::{anyhow, Error};
 use poise::serenity_prelude as serenity;
@@ -23,6 +24,7 @@ use tracing::{debug, info, warn};
 
 pub mod checks;
 pub mod commands;
+pub mod config;
 pub mod helpers;
 pub mod types;
 
@@ -40,10 +42,13 @@ async fn serenity(
 		.await
 		.expect("Failed to run migrations");
 
+	let config = Config::new(&secret_store)?;
+	let prefix = config.prefix.clone();
+
 	let framework = poise::Framework::builder()
 		.setup(move |ctx, ready, framework| {
 			Box::pin(async move {
-				let data = Data::new(&secret_store, pool)?;
+				let data = Data::new(&secret_store, pool, config)?;
 
 				debug!("Registering commands...");
 				poise::builtins::register_in_guild(
@@ -67,6 +72,7 @@ async fn serenity(
 				commands::crates::crate_(),
 				commands::crates::doc(),
 				commands::godbolt::godbolt(),
+				commands::godbolt::asmstd(),
 				commands::godbolt::mca(),
 				commands::godbolt::llvmir(),
 				commands::godbolt::targets(),
@@ -85,16 +91,23 @@ async fn serenity(
 				commands::modmail::modmail_context_menu_for_user(),
 				commands::playground::play(),
 				commands::playground::playwarn(),
+				commands::playground::example(),
 				commands::playground::eval(),
+				commands::playground::playflags(),
 				commands::playground::miri(),
 				commands::playground::expand(),
 				commands::playground::clippy(),
 				commands::playground::fmt(),
+				commands::playground::fmtrun(),
+				commands::playground::editiondiff(),
+				commands::playground::funcs(),
 				commands::playground::microbench(),
 				commands::playground::procmacro(),
+				commands::playground::playhealth(),
+				commands::playground::get(),
 			],
 			prefix_options: poise::PrefixFrameworkOptions {
-				prefix: Some("?".into()),
+				prefix: Some(prefix),
 				additional_prefixes: vec![
 					poise::Prefix::Literal("🦀 "),
 					poise::Prefix::Literal("🦀"),
@@ -121,18 +134,9 @@ async fn serenity(
 			on_error: |error| {
 				Box::pin(async move {
 					warn!("Encountered error: {:?}", error);
-					const FAILED_CODEBLOCK: &str = "\
-Missing code block. Please use the following markdown:
-`` `code here` ``
-or
-```ansi
-`\x1b[0m`\x1b[0m`rust
-code here
-`\x1b[0m`\x1b[0m`
-```";
 					if let poise::FrameworkError::ArgumentParse { error, ctx, .. } = error {
 						let response = if error.is::<poise::CodeBlockError>() {
-							FAILED_CODEBLOCK.to_owned()
+							helpers::missing_code_block_reply(ctx)
 						} else if let Some(multiline_help) = &ctx.command().help_text {
 							format!("**{error}**\n{multiline_help}")
 						} else {
@@ -144,7 +148,7 @@ code here
 						}
 					} else if let poise::FrameworkError::Command { ctx, error, .. } = error {
 						if error.is::<poise::CodeBlockError>() {
-							if let Err(e) = ctx.say(FAILED_CODEBLOCK.to_owned()).await {
+							if let Err(e) = ctx.say(helpers::missing_code_block_reply(ctx)).await {
 								warn!("{}", e)
 							}
 						}
@@ -179,7 +183,15 @@ code here
 				})
 			},
 			// Every command invocation must pass this check to continue execution
-			command_check: Some(|_ctx| Box::pin(async move { Ok(true) })),
+			command_check: Some(|ctx| {
+				Box::pin(async move {
+					// Playground commands can be restricted to certain channels
+					let allowed_channels = &ctx.data().config.playground_allowed_channels;
+					Ok(ctx.command().category.as_deref() != Some("Playground")
+						|| allowed_channels.is_empty()
+						|| allowed_channels.contains(&ctx.channel_id()))
+				})
+			}),
 			// Enforce command checks even for owners (enforced by default)
 			// Set to true to bypass checks, which is useful for testing
 			skip_checks_for_owners: false,
@@ -5,10 +5,12 @@ use poise::serenity_prelude as serenity;
 use shuttle_runtime::SecretStore;
 
 use crate::commands;
+use crate::config::Config;
 
 #[derive(Debug)]
 pub struct Data {
 	pub database: sqlx::PgPool,
+	pub config: Config,
 	pub discord_guild_id: serenity::GuildId,
 	pub application_id: serenity::UserId,
 	pub mod_role_id: serenity::RoleId,
@@ -18,12 +20,19 @@ pub struct Data {
 	pub bot_start_time: std::time::Instant,
 	pub http: reqwest::Client,
 	pub godbolt_metadata: std::sync::Mutex<commands::godbolt::GodboltMetadata>,
+	pub playground_health: std::sync::Mutex<commands::playground::PlaygroundHealth>,
+	pub playground_edit_runs: std::sync::Mutex<commands::playground::EditDebounce>,
+	pub playground_results: std::sync::Mutex<commands::playground::ResultCache>,
+	/// Output of the last playground run in each channel, for `diffprev=true`
+	pub playground_last_outputs:
+		std::sync::Mutex<std::collections::HashMap<serenity::ChannelId, String>>,
 }
 
 impl Data {
-	pub fn new(secret_store: &SecretStore, database: sqlx::PgPool) -> Result<Self> {
+	pub fn new(secret_store: &SecretStore, database: sqlx::PgPool, config: Config) -> Result<Self> {
 		Ok(Self {
 			database,
+			config,
 			discord_guild_id: secret_store
 				.get("DISCORD_GUILD")
 				.ok_or(anyhow!(
@@ -61,6 +70,10 @@ impl Data {
 			bot_start_time: std::time::Instant::now(),
 			http: reqwest::Client::new(),
 			godbolt_metadata: std::sync::Mutex::new(commands::godbolt::GodboltMetadata::default()),
+			playground_health: std::sync::Mutex::default(),
+			playground_edit_runs: std::sync::Mutex::default(),
+			playground_results: std::sync::Mutex::default(),
+			playground_last_outputs: std::sync::Mutex::default(),
 		})
 	}
 }